@@ -9,7 +9,7 @@ layout(push_constant) uniform PushConstants {
 layout(location = 0) out vec4 out_color;
 
 const int MAX_RAY_STEPS = 512;
-const float SCALE = 5.0;
+// @param float SCALE = 5.0 [1, 10]
 
 float sdSphere(vec3 p, float d)
 {
@@ -45,11 +45,12 @@ vec3 calc_normal(in vec3 p)
         e.xxx * map(p + e.xxx));
 }
 
+// @param vec3 light = (0, 2, 0) [-10, 10]
+// @param float light_intensity = 5.0 [0, 20]
+
 vec3 diffuse(vec3 p)
 {
     vec3 normal = calc_normal(p);
-    vec3 light = vec3(0, 2, 0);
-    float light_intensity = 5.0;
 
     // Calculate diffuse lighting by taking the dot product of
     // the light direction (light-p) and the normal
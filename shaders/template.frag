@@ -1,16 +1,30 @@
 #version 450
 
+// Any prefix of this block can be declared. Shaders without a #version directive are treated as
+// Shadertoy shaders and get iTime, iResolution etc. mapped onto it instead.
 layout(push_constant) uniform PushConstants {
     float time;
     float res_x;
     float res_y;
+    float time_delta;
+    vec4 mouse;
+    vec4 date;
+    int frame;
+    float frame_rate;
 } constants;
 
+// Parameters tweakable at runtime, with their default and range. They become members of a uniform
+// block declared by vxtr, so they must not be declared again.
+// @param float speed = 1.0 [0, 10]
+
+// Inputs of a multipass project, see project.toml. Unused channels sample as black.
+// layout(set = 0, binding = 0) uniform sampler2D channel0;
+
 layout(location = 0) out vec4 out_color;
 
 void main()
 {
-    float time = constants.time;
+    float time = constants.time * speed;
     // For shadertoy:
     // vec2 frag_coord = vec2(gl_FragCoord.x, constants.res_y - gl_FragCoord.y);
     vec2 frag_coord = gl_FragCoord.xy;
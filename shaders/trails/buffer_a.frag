@@ -0,0 +1,18 @@
+// Accumulates a moving dot over its own previous frame
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord)
+{
+    vec2 uv = fragCoord / iResolution.xy;
+    vec3 previous = texture(iChannel0, uv).rgb;
+
+    vec2 center = iResolution.xy * (0.5 + 0.35 * vec2(cos(iTime * 1.3), sin(iTime * 2.1)));
+
+    if (iMouse.z > 0.0) {
+        center = iMouse.xy;
+    }
+
+    float spot = smoothstep(12.0, 8.0, length(fragCoord - center));
+    vec3 color = 0.5 + 0.5 * cos(iTime + vec3(0.0, 2.0, 4.0));
+
+    fragColor = vec4(max(previous * 0.98, spot * color), 1.0);
+}
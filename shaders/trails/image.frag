@@ -0,0 +1,6 @@
+void mainImage(out vec4 fragColor, in vec2 fragCoord)
+{
+    vec2 uv = fragCoord / iResolution.xy;
+
+    fragColor = vec4(texture(iChannel0, uv).rgb, 1.0);
+}
@@ -0,0 +1,516 @@
+use crate::clock;
+use crate::config::PresentMode;
+
+use std::path::PathBuf;
+
+/// Longest export accepted, a bit over 46 hours at 60 fps
+const MAX_EXPORT_FRAMES: u32 = 10_000_000;
+
+const USAGE: &str = "\
+Usage: vxtr [OPTIONS] [SHADER]
+       vxtr render [--shader SHADER] [--time T] [--size WxH] [--out FILE]
+       vxtr export [--shader SHADER] [--fps N] [--duration T] [--size WxH] [--out DIR | --y4m]
+       vxtr bench [OPTIONS] [SHADER]...
+
+Arguments:
+  [SHADER]           Fragment shader to run, either GLSL source or precompiled SPIR-V (.spv).
+                     GLSL without a #version directive is run as a Shadertoy shader.
+                     A directory with a project.toml, or the file itself, runs a multipass project.
+
+Options:
+  -l, --list         List shaders and projects in the shaders/ directory
+  -b, --benchmark    Same as the bench command with default options
+  --size WxH         Size of the window in pixels [default: 800x600]
+  --time T           Time in seconds to start at [default: 0]
+  --speed X          Rate at which time passes, from 0.1 to 10. Negative plays in reverse.
+                     [default: 1]
+  --paused           Start with time paused
+  --preset NAME      Start with the parameter values saved as NAME, also the preset that F5 and
+                     F9 use [default: default]
+  --config FILE      Configuration file to load after the user's and the current directory's
+                     vxtr.toml
+  --print-config     Print the configuration and the files it was loaded from, then exit
+  --updates-per-second N
+                     Rate at which time advances between frames [default: 60]
+  --frames-in-flight N
+                     Number of frames the CPU can prepare ahead of the GPU [default: 2]
+  --present-mode MODE
+                     immediate, mailbox, fifo (vsync) or fifo_relaxed. Falls back to fifo if the
+                     device doesn't support it. [default: the first supported of immediate,
+                     fifo_relaxed, mailbox and fifo]
+  --monitor N        Monitor to go fullscreen on, counting from 0 [default: the primary one]
+  --device DEVICE    GPU to render on: its index or UUID as listed at startup, or part of its
+                     name [default: the first suitable discrete, integrated, virtual or CPU one]
+  --validation       Enable VK_LAYER_KHRONOS_validation and print the problems it finds. More
+                     layers can be set in the configuration file.
+  -h, --help         Print this message
+
+Keys:
+  Space              Pause or resume time
+  Up, Down           Speed time up or slow it down
+  R                  Reverse time
+  Left, Right        Pause and step one update back or forward
+  Shift+Left/Right   Skip one second back or forward
+  Home               Jump to the start
+  0-9 . Enter        Type a time in seconds and jump to it. Escape cancels.
+  Tab, Shift+Tab     Select the next or previous shader parameter
+  ], [               Increase or decrease the selected parameter, ten times faster with Shift
+  \\                  Reset the selected parameter
+  P                  Print the values of all parameters
+  F5                 Save the parameters as a preset, in SHADER.presets.toml or presets.toml next
+                     to the project's manifest
+  F9                 Load the preset
+  V                  Toggle vsync
+  F11                Cycle between windowed, borderless fullscreen and exclusive fullscreen
+  Alt+Enter          Toggle between windowed and borderless fullscreen
+  F12                Save a screenshot to the current directory
+  Escape             Quit
+
+Render options:
+  --shader SHADER    Fragment shader to render, same as [SHADER]
+  --time T           Time in seconds to render the frame at [default: 0]
+  --size WxH         Size of the image in pixels [default: 800x600]
+  --out FILE         PNG file to write [default: frame.png]
+
+Export options:
+  --shader SHADER    Fragment shader to render, same as [SHADER]
+  --fps N            Frames per second, time advances by exactly 1/N per frame [default: 60]
+  --duration T       Length of the exported video in seconds [default: 10]
+  --size WxH         Size of the frames in pixels [default: 800x600]
+  --out DIR          Directory to write numbered PNG frames to [default: frames]
+  --y4m              Write a Y4M stream to stdout instead of PNG frames
+
+Bench options:
+  [SHADER]...        Shaders or projects to benchmark one after another, under the same settings
+  --shader SHADER    Same as [SHADER]
+  --frames N         Number of measured frames [default: 1000]
+  --duration T       Measure frames for T seconds instead of a fixed number
+  --warmup N         Frames rendered before measuring [default: 100]
+  --size WxH         Size of the rendered frames in pixels [default: 800x600]
+  --format FORMAT    Output format: text, json (summary and per-frame times) or csv (per-frame
+                     times, or one summary row per shader when there are several) [default: text]
+  --out FILE         File to write the results to [default: stdout]
+  --baseline FILE    Median frame times to compare against. Shaders missing from it are added.
+                     [default: bench-baseline.toml]
+  --threshold P      Percentage by which a shader can get slower than its baseline before it
+                     counts as a regression [default: 5]
+  --save-baseline    Replace the baseline of the benchmarked shaders with the new results";
+
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Args {
+    pub shader: Option<PathBuf>,
+    pub list: bool,
+    pub command: Command,
+    pub config: Option<PathBuf>,
+    pub print_config: bool,
+    /// Initial state of the time controls
+    pub start_time: f64,
+    pub speed: Option<f64>,
+    pub paused: bool,
+    /// Parameter preset to start with
+    pub preset: Option<String>,
+    /// Overrides for the configuration files
+    pub size: Option<(u32, u32)>,
+    pub updates_per_second: Option<u32>,
+    pub frames_in_flight: Option<usize>,
+    pub present_mode: Option<PresentMode>,
+    pub monitor: Option<usize>,
+    pub device: Option<String>,
+    pub validation: bool,
+}
+
+#[derive(Default)]
+pub enum Command {
+    /// Open a window and render continuously
+    #[default]
+    Run,
+    /// Render a single frame without opening a window
+    Render(RenderArgs),
+    /// Render a sequence of frames at a fixed rate without opening a window
+    Export(ExportArgs),
+    /// Render frames in a window as fast as possible and report their timings
+    Bench(BenchArgs),
+}
+
+pub struct RenderArgs {
+    pub time: f64,
+    pub width: u32,
+    pub height: u32,
+    pub out: PathBuf,
+}
+
+pub struct ExportArgs {
+    pub fps: u32,
+    pub duration: f64,
+    pub width: u32,
+    pub height: u32,
+    pub out: PathBuf,
+    pub y4m: bool,
+}
+
+pub struct BenchArgs {
+    pub shaders: Vec<PathBuf>,
+    pub limit: BenchLimit,
+    pub warmup: u64,
+    pub width: u32,
+    pub height: u32,
+    pub format: BenchFormat,
+    pub out: Option<PathBuf>,
+    pub baseline: PathBuf,
+    /// Percent
+    pub threshold: f64,
+    pub save_baseline: bool,
+}
+
+/// When to stop measuring
+#[derive(Clone, Copy)]
+pub enum BenchLimit {
+    Frames(u64),
+    Seconds(f64),
+}
+
+#[derive(Clone, Copy)]
+pub enum BenchFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Default for RenderArgs {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            width: 800,
+            height: 600,
+            out: PathBuf::from("frame.png"),
+        }
+    }
+}
+
+impl Default for ExportArgs {
+    fn default() -> Self {
+        Self {
+            fps: 60,
+            duration: 10.0,
+            width: 800,
+            height: 600,
+            out: PathBuf::from("frames"),
+            y4m: false,
+        }
+    }
+}
+
+impl Default for BenchArgs {
+    fn default() -> Self {
+        Self {
+            shaders: Vec::new(),
+            limit: BenchLimit::Frames(1000),
+            warmup: 100,
+            width: 800,
+            height: 600,
+            format: BenchFormat::Text,
+            out: None,
+            baseline: PathBuf::from("bench-baseline.toml"),
+            threshold: 5.0,
+            save_baseline: false,
+        }
+    }
+}
+
+impl Args {
+    /// Parses command line arguments. Prints usage and exits on `--help` or on error.
+    pub fn parse() -> Self {
+        match Self::try_parse(std::env::args().skip(1)) {
+            Ok(args) => args,
+            Err(message) => {
+                eprintln!("Error: {}\n\n{}", message, USAGE);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn try_parse<I: Iterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.peekable();
+
+        if args.next_if(|arg| arg == "render").is_some() {
+            parsed.command = Command::Render(RenderArgs::default());
+        } else if args.next_if(|arg| arg == "export").is_some() {
+            parsed.command = Command::Export(ExportArgs::default());
+        } else if args.next_if(|arg| arg == "bench").is_some() {
+            parsed.command = Command::Bench(BenchArgs::default());
+        }
+
+        while let Some(arg) = args.next() {
+            if let Command::Bench(bench) = &mut parsed.command {
+                if bench.parse_option(&arg, &mut args)? {
+                    continue;
+                }
+            }
+
+            if let Command::Run = parsed.command {
+                if parsed.parse_run_option(&arg, &mut args)? {
+                    continue;
+                }
+            }
+
+            match (arg.as_str(), &mut parsed.command) {
+                ("-l" | "--list", Command::Run) => parsed.list = true,
+                ("-b" | "--benchmark", Command::Run) => {
+                    parsed.command = Command::Bench(BenchArgs {
+                        shaders: parsed.shader.take().into_iter().collect(),
+                        ..BenchArgs::default()
+                    });
+                }
+                ("--config", _) => {
+                    parsed.config = Some(PathBuf::from(next_value(&mut args, &arg)?));
+                }
+                ("--print-config", _) => parsed.print_config = true,
+                ("--updates-per-second", _) => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.updates_per_second = Some(parse_number(&value, "update rate")?);
+                }
+                ("--frames-in-flight", _) => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.frames_in_flight = Some(parse_number(&value, "frame count")?);
+                }
+                ("--present-mode", _) => {
+                    let value = next_value(&mut args, &arg)?;
+                    let mode = PresentMode::from_name(&value)
+                        .ok_or_else(|| format!("unknown present mode \"{}\"", value))?;
+
+                    parsed.present_mode = Some(mode);
+                }
+                ("--monitor", _) => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.monitor = Some(parse_number(&value, "monitor index")?);
+                }
+                ("--device", _) => parsed.device = Some(next_value(&mut args, &arg)?),
+                ("--validation", _) => parsed.validation = true,
+                ("--size", Command::Run) => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.size = Some(parse_size(&value)?);
+                }
+                ("-h" | "--help", _) => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                ("--shader", Command::Render(_) | Command::Export(_)) => {
+                    let value = next_value(&mut args, &arg)?;
+                    set_shader(&mut parsed, value)?;
+                }
+                ("--time", Command::Render(render)) => {
+                    let value = next_value(&mut args, &arg)?;
+                    render.time = parse_number(&value, "time")?;
+                }
+                (
+                    "--size",
+                    Command::Render(RenderArgs { width, height, .. })
+                    | Command::Export(ExportArgs { width, height, .. })
+                    | Command::Bench(BenchArgs { width, height, .. }),
+                ) => {
+                    let value = next_value(&mut args, &arg)?;
+                    (*width, *height) = parse_size(&value)?;
+                }
+                (
+                    "--out",
+                    Command::Render(RenderArgs { out, .. })
+                    | Command::Export(ExportArgs { out, .. }),
+                ) => *out = PathBuf::from(next_value(&mut args, &arg)?),
+                ("--fps", Command::Export(export)) => {
+                    export.fps = parse_frame_rate(&next_value(&mut args, &arg)?)?;
+                }
+                ("--duration", Command::Export(export)) => {
+                    export.duration = parse_duration(&next_value(&mut args, &arg)?)?;
+                }
+                ("--y4m", Command::Export(export)) => export.y4m = true,
+                _ if arg.starts_with('-') => return Err(format!("unknown option \"{}\"", arg)),
+                _ => set_shader(&mut parsed, arg)?,
+            }
+        }
+
+        // Duration and frame rate can come in either order
+        if let Command::Export(export) = &parsed.command {
+            export.check_length()?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns whether `arg` was one of the options for the initial state of a window
+    fn parse_run_option<I: Iterator<Item = String>>(
+        &mut self,
+        arg: &str,
+        args: &mut I,
+    ) -> Result<bool, String> {
+        match arg {
+            "--time" => {
+                let value = next_value(args, arg)?;
+                self.start_time = parse_number(&value, "time")?;
+            }
+            "--speed" => {
+                let value = next_value(args, arg)?;
+                self.speed = Some(parse_speed(&value)?);
+            }
+            "--paused" => self.paused = true,
+            "--preset" => self.preset = Some(next_value(args, arg)?),
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+}
+
+impl BenchArgs {
+    /// Returns whether `arg` was an option or a shader for the bench command
+    fn parse_option<I: Iterator<Item = String>>(
+        &mut self,
+        arg: &str,
+        args: &mut I,
+    ) -> Result<bool, String> {
+        match arg {
+            "--shader" => self.shaders.push(PathBuf::from(next_value(args, arg)?)),
+            "--out" => {
+                self.out = Some(PathBuf::from(next_value(args, arg)?));
+            }
+            "--frames" => {
+                let value = next_value(args, arg)?;
+                let frames = parse_number(&value, "frame count")?;
+
+                if frames == 0 {
+                    return Err(format!("invalid frame count \"{}\"", value));
+                }
+
+                self.limit = BenchLimit::Frames(frames);
+            }
+            "--duration" => {
+                let duration = parse_duration(&next_value(args, arg)?)?;
+                self.limit = BenchLimit::Seconds(duration);
+            }
+            "--warmup" => {
+                let value = next_value(args, arg)?;
+                self.warmup = parse_number(&value, "warm-up frame count")?;
+            }
+            "--format" => {
+                self.format = match next_value(args, arg)?.as_str() {
+                    "text" => BenchFormat::Text,
+                    "json" => BenchFormat::Json,
+                    "csv" => BenchFormat::Csv,
+                    value => return Err(format!("unknown format \"{}\"", value)),
+                };
+            }
+            "--baseline" => {
+                self.baseline = PathBuf::from(next_value(args, arg)?);
+            }
+            "--threshold" => {
+                let value = next_value(args, arg)?;
+                let threshold: f64 = parse_number(&value, "threshold")?;
+
+                if !threshold.is_finite() || threshold < 0.0 {
+                    return Err(format!("invalid threshold \"{}\"", value));
+                }
+
+                self.threshold = threshold;
+            }
+            "--save-baseline" => self.save_baseline = true,
+            _ if arg.starts_with('-') => return Ok(false),
+            _ => self.shaders.push(PathBuf::from(arg)),
+        }
+
+        Ok(true)
+    }
+}
+
+impl ExportArgs {
+    /// Number of frames needed to cover the duration, at least one. Parsing makes sure it's at
+    /// most `MAX_EXPORT_FRAMES`.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn frame_count(&self) -> u64 {
+        self.frames().max(1.0) as u64
+    }
+
+    fn check_length(&self) -> Result<(), String> {
+        if self.frames() > f64::from(MAX_EXPORT_FRAMES) {
+            return Err(format!(
+                "export is too long, {} frames is more than the limit of {}",
+                self.frames(),
+                MAX_EXPORT_FRAMES
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn frames(&self) -> f64 {
+        (self.duration * f64::from(self.fps)).round()
+    }
+}
+
+fn next_value<I: Iterator<Item = String>>(args: &mut I, option: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("missing value for \"{}\"", option))
+}
+
+fn set_shader(parsed: &mut Args, value: String) -> Result<(), String> {
+    if parsed.shader.is_some() {
+        return Err(format!("unexpected argument \"{}\"", value));
+    }
+
+    parsed.shader = Some(PathBuf::from(value));
+
+    Ok(())
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid {} \"{}\"", what, value))
+}
+
+fn parse_frame_rate(value: &str) -> Result<u32, String> {
+    let fps: u32 = parse_number(value, "frame rate")?;
+
+    if fps == 0 {
+        return Err(format!("invalid frame rate \"{}\"", value));
+    }
+
+    Ok(fps)
+}
+
+fn parse_duration(value: &str) -> Result<f64, String> {
+    let duration: f64 = parse_number(value, "duration")?;
+
+    if !duration.is_finite() || duration <= 0.0 {
+        return Err(format!("invalid duration \"{}\"", value));
+    }
+
+    Ok(duration)
+}
+
+fn parse_speed(value: &str) -> Result<f64, String> {
+    let speed: f64 = parse_number(value, "speed")?;
+
+    if !(clock::MIN_SPEED..=clock::MAX_SPEED).contains(&speed.abs()) {
+        return Err(format!(
+            "speed {} is out of range, expected {} to {}, or negative to play in reverse",
+            value,
+            clock::MIN_SPEED,
+            clock::MAX_SPEED
+        ));
+    }
+
+    Ok(speed)
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid size \"{}\", expected WIDTHxHEIGHT", value);
+
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    let width: u32 = width.parse().map_err(|_| invalid())?;
+    let height: u32 = height.parse().map_err(|_| invalid())?;
+
+    // GLFW takes window sizes as i32
+    if width == 0 || height == 0 || i32::try_from(width.max(height)).is_err() {
+        return Err(invalid());
+    }
+
+    Ok((width, height))
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Median frame times of benchmarked shaders, kept between runs to catch regressions. Only
+/// results measured at the same size are comparable.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    width: u32,
+    height: u32,
+    /// Keyed by the shader path as given on the command line
+    #[serde(default)]
+    shaders: BTreeMap<String, Entry>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub cpu_median_ms: f64,
+    /// Missing if the device didn't support timestamps
+    pub gpu_median_ms: Option<f64>,
+}
+
+impl Baseline {
+    /// Returns an empty baseline if the file doesn't exist yet
+    pub fn load(path: &Path, width: u32, height: u32) -> Result<Self, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    width,
+                    height,
+                    shaders: BTreeMap::new(),
+                });
+            }
+            Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+        };
+
+        let baseline: Self = toml::from_str(&contents)
+            .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+
+        if (baseline.width, baseline.height) != (width, height) {
+            return Err(format!(
+                "{} was measured at {}x{}, use the same --size or another --baseline",
+                path.display(),
+                baseline.width,
+                baseline.height
+            ));
+        }
+
+        Ok(baseline)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = toml::to_string_pretty(self)
+            .map_err(|e| format!("failed to serialize {}: {}", path.display(), e))?;
+
+        fs::write(path, contents).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+    }
+
+    /// Percentage by which `entry` is slower than the baseline of `shader`, along with the name of
+    /// the compared time. GPU times are preferred when both have them, as they are less noisy.
+    pub fn change(&self, shader: &str, entry: &Entry) -> Option<(f64, &'static str)> {
+        let old = self.shaders.get(shader)?;
+
+        let (old_ms, new_ms, name) = match (old.gpu_median_ms, entry.gpu_median_ms) {
+            (Some(old_ms), Some(new_ms)) => (old_ms, new_ms, "GPU median"),
+            _ => (old.cpu_median_ms, entry.cpu_median_ms, "CPU median"),
+        };
+
+        Some(((new_ms / old_ms - 1.0) * 100.0, name))
+    }
+
+    pub fn set(&mut self, shader: &str, entry: Entry) {
+        self.shaders.insert(shader.to_string(), entry);
+    }
+}
@@ -0,0 +1,457 @@
+use crate::args::{BenchArgs, BenchFormat, BenchLimit};
+use crate::baseline::{self, Baseline};
+use crate::config::RenderConfig;
+use crate::project::{CompiledProject, Project};
+use crate::state::State;
+
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Shader time advances by this much every frame regardless of how long the frame took, so that
+/// every run renders the same frames
+const TIME_STEP: f64 = 1.0 / 60.0;
+
+/// Timings of the measured frames, in milliseconds
+pub struct BenchResults {
+    /// Image pass is last
+    pub pass_names: Vec<String>,
+    /// Wall-clock time between the ends of consecutive frames
+    pub cpu_times: Vec<f64>,
+    /// Time each pass took on the GPU, for every frame. Empty if the device doesn't support
+    /// timestamps.
+    pub gpu_times: Vec<Vec<f64>>,
+}
+
+pub struct Stats {
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Benchmarks each shader offscreen, writes the report and compares the results against
+/// the baseline. Fails if any shader regressed.
+pub fn bench(shaders: &[PathBuf], args: &BenchArgs, config: &RenderConfig) -> Result<(), String> {
+    let mut baseline = Baseline::load(&args.baseline, args.width, args.height)?;
+    let mut runs = Vec::with_capacity(shaders.len());
+
+    for shader in shaders {
+        let project = Project::load(shader).and_then(|project| project.compile())?;
+
+        eprintln!("Benchmarking {}", shader.display());
+
+        runs.push((shader.as_path(), measure(project, args, config)?));
+    }
+
+    let report = match (args.format, runs.as_slice()) {
+        (BenchFormat::Text, [(shader, results)]) => results.to_text(shader, args),
+        (BenchFormat::Json, [(shader, results)]) => results.to_json(shader, args),
+        (BenchFormat::Csv, [(_, results)]) => results.to_csv(),
+        (BenchFormat::Text, _) => comparison_table(&runs, args),
+        (BenchFormat::Json, _) => {
+            let reports: Vec<String> =
+                runs.iter().map(|(shader, results)| results.to_json(shader, args)).collect();
+
+            format!("[\n{}]\n", reports.join(",\n"))
+        }
+        (BenchFormat::Csv, _) => summary_csv(&runs),
+    };
+
+    match &args.out {
+        Some(path) => fs::write(path, report)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?,
+        None => print!("{}", report),
+    }
+
+    let mut regressed = Vec::new();
+
+    for (shader, results) in &runs {
+        let name = shader.to_string_lossy();
+        let entry = results.baseline_entry();
+
+        let Some((change, compared)) = baseline.change(&name, &entry) else {
+            eprintln!("{}: added to the baseline", name);
+            baseline.set(&name, entry);
+            continue;
+        };
+
+        if change > args.threshold {
+            eprintln!(
+                "{}: {} is {:+.1}% compared to the baseline, regressed",
+                name, compared, change
+            );
+            regressed.push(name.to_string());
+        } else {
+            eprintln!("{}: {} is {:+.1}% compared to the baseline", name, compared, change);
+        }
+
+        if args.save_baseline {
+            baseline.set(&name, entry);
+        }
+    }
+
+    baseline.save(&args.baseline)?;
+
+    if !regressed.is_empty() {
+        return Err(format!(
+            "slower than the baseline by more than {}%: {}",
+            args.threshold,
+            regressed.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Renders the warm-up frames, then the measured ones. Rendering is offscreen so that the size
+/// stays fixed no matter what the window manager does; each frame is waited for before the next.
+fn measure(
+    project: CompiledProject,
+    args: &BenchArgs,
+    config: &RenderConfig,
+) -> Result<BenchResults, String> {
+    let mut state = State::new_offscreen(args.width, args.height, project, config)?;
+    state.keep_gpu_history();
+
+    for frame in 0..args.warmup {
+        render_frame(&mut state, frame)?;
+    }
+
+    state.take_gpu_history();
+
+    let start_time = Instant::now();
+    let mut last_frame_end = start_time;
+    let mut cpu_times = Vec::new();
+    let mut frame = args.warmup;
+
+    while !is_done(args.limit, cpu_times.len(), (last_frame_end - start_time).as_secs_f64()) {
+        render_frame(&mut state, frame)?;
+        frame += 1;
+
+        let frame_end = Instant::now();
+
+        cpu_times.push((frame_end - last_frame_end).as_secs_f64() * 1000.0);
+        last_frame_end = frame_end;
+    }
+
+    if cpu_times.is_empty() {
+        return Err("no frames were measured".to_string());
+    }
+
+    Ok(BenchResults {
+        pass_names: state.pass_names().into_iter().map(str::to_string).collect(),
+        cpu_times,
+        gpu_times: state.take_gpu_history(),
+    })
+}
+
+fn render_frame(state: &mut State, frame: u64) -> Result<(), String> {
+    #[allow(clippy::cast_precision_loss)]
+    let time = frame as f64 * TIME_STEP;
+
+    state.update(TIME_STEP, time);
+    state.render_offscreen()
+}
+
+fn is_done(limit: BenchLimit, frames: usize, seconds: f64) -> bool {
+    match limit {
+        BenchLimit::Frames(count) => frames as u64 >= count,
+        BenchLimit::Seconds(duration) => seconds >= duration,
+    }
+}
+
+impl BenchResults {
+    pub fn cpu_stats(&self) -> Stats {
+        Stats::new(&self.cpu_times)
+    }
+
+    /// Total time of all passes, or `None` without timestamp support
+    pub fn gpu_stats(&self) -> Option<Stats> {
+        let totals: Vec<f64> = self.gpu_times.iter().map(|passes| passes.iter().sum()).collect();
+
+        (!totals.is_empty()).then(|| Stats::new(&totals))
+    }
+
+    pub fn baseline_entry(&self) -> baseline::Entry {
+        baseline::Entry {
+            cpu_median_ms: self.cpu_stats().median,
+            gpu_median_ms: self.gpu_stats().map(|stats| stats.median),
+        }
+    }
+
+    pub fn pass_stats(&self, pass: usize) -> Option<Stats> {
+        let times: Vec<f64> = self.gpu_times.iter().map(|passes| passes[pass]).collect();
+
+        (!times.is_empty()).then(|| Stats::new(&times))
+    }
+
+    fn to_text(&self, shader: &Path, args: &BenchArgs) -> String {
+        let mut text = String::new();
+
+        let _ = writeln!(text, "Shader: {}", shader.display());
+        let _ = writeln!(
+            text,
+            "Size: {}x{}, {} warm-up frames, {} measured frames\n",
+            args.width,
+            args.height,
+            args.warmup,
+            self.cpu_times.len()
+        );
+        let _ = writeln!(
+            text,
+            "{:<16} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "ms", "mean", "median", "p95", "p99", "min", "max"
+        );
+
+        write_row(&mut text, "CPU frame", &self.cpu_stats());
+
+        let Some(gpu_stats) = self.gpu_stats() else {
+            text.push_str("GPU timestamps are not supported on this device\n");
+            return text;
+        };
+
+        write_row(&mut text, "GPU total", &gpu_stats);
+
+        if self.pass_names.len() > 1 {
+            for (i, name) in self.pass_names.iter().enumerate() {
+                write_row(&mut text, &format!("  {}", name), &self.pass_stats(i).unwrap());
+            }
+        }
+
+        text
+    }
+
+    fn to_json(&self, shader: &Path, args: &BenchArgs) -> String {
+        let mut json = String::from("{\n");
+
+        let _ = writeln!(json, "  \"shader\": {},", json_string(&shader.to_string_lossy()));
+        let _ = writeln!(json, "  \"width\": {},", args.width);
+        let _ = writeln!(json, "  \"height\": {},", args.height);
+        let _ = writeln!(json, "  \"warmup_frames\": {},", args.warmup);
+        let _ = writeln!(json, "  \"frames\": {},", self.cpu_times.len());
+        let _ = writeln!(json, "  \"cpu\": {},", self.cpu_stats().to_json());
+
+        let gpu = self.gpu_stats().map_or_else(|| "null".to_string(), |stats| stats.to_json());
+        let _ = writeln!(json, "  \"gpu\": {},", gpu);
+
+        let passes: Vec<String> = self
+            .pass_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let gpu =
+                    self.pass_stats(i).map_or_else(|| "null".to_string(), |stats| stats.to_json());
+
+                format!("    {{\"name\": {}, \"gpu\": {}}}", json_string(name), gpu)
+            })
+            .collect();
+
+        let _ = writeln!(json, "  \"passes\": [\n{}\n  ],", passes.join(",\n"));
+
+        let frames: Vec<String> = self
+            .cpu_times
+            .iter()
+            .enumerate()
+            .map(|(i, cpu)| match self.gpu_times.get(i) {
+                Some(passes) => {
+                    let total: f64 = passes.iter().sum();
+                    let passes: Vec<String> = passes.iter().map(f64::to_string).collect();
+
+                    format!(
+                        "    {{\"cpu\": {}, \"gpu\": {}, \"passes\": [{}]}}",
+                        cpu,
+                        total,
+                        passes.join(", ")
+                    )
+                }
+                None => format!("    {{\"cpu\": {}, \"gpu\": null, \"passes\": null}}", cpu),
+            })
+            .collect();
+
+        let _ = writeln!(json, "  \"frame_times\": [\n{}\n  ]", frames.join(",\n"));
+
+        json.push_str("}\n");
+        json
+    }
+
+    /// One row per frame, with the time of each pass in its own column
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("frame,cpu_ms,gpu_ms");
+
+        for name in &self.pass_names {
+            let _ = write!(csv, ",{}", csv_field(&format!("{}_ms", name)));
+        }
+
+        csv.push('\n');
+
+        for (i, cpu) in self.cpu_times.iter().enumerate() {
+            let _ = write!(csv, "{},{}", i, cpu);
+
+            match self.gpu_times.get(i) {
+                Some(passes) => {
+                    let _ = write!(csv, ",{}", passes.iter().sum::<f64>());
+
+                    for time in passes {
+                        let _ = write!(csv, ",{}", time);
+                    }
+                }
+                None => csv.push_str(&",".repeat(self.pass_names.len() + 1)),
+            }
+
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+impl Stats {
+    /// `samples` must not be empty
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        // Both indices are the same for an odd number of samples
+        let median = f64::midpoint(sorted[(sorted.len() - 1) / 2], sorted[sorted.len() / 2]);
+
+        Self {
+            mean,
+            median,
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+        }
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"mean\": {}, \"median\": {}, \"p95\": {}, \"p99\": {}, \"min\": {}, \"max\": {}}}",
+            self.mean, self.median, self.p95, self.p99, self.min, self.max
+        )
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Medians and 95th percentiles of every shader
+fn comparison_table(runs: &[(&Path, BenchResults)], args: &BenchArgs) -> String {
+    let names: Vec<String> = runs.iter().map(|(shader, _)| shader.display().to_string()).collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0).max("shader".len());
+
+    let mut text = String::new();
+
+    let _ =
+        writeln!(text, "Size: {}x{}, {} warm-up frames\n", args.width, args.height, args.warmup);
+    let _ = writeln!(
+        text,
+        "{:<width$} {:>8} {:>10} {:>8} {:>10} {:>8}",
+        "shader", "frames", "cpu median", "cpu p95", "gpu median", "gpu p95"
+    );
+
+    for (name, (_, results)) in names.iter().zip(runs) {
+        let cpu = results.cpu_stats();
+        let (gpu_median, gpu_p95) = match results.gpu_stats() {
+            Some(gpu) => (format!("{:.3}", gpu.median), format!("{:.3}", gpu.p95)),
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        let _ = writeln!(
+            text,
+            "{:<width$} {:>8} {:>10.3} {:>8.3} {:>10} {:>8}",
+            name,
+            results.cpu_times.len(),
+            cpu.median,
+            cpu.p95,
+            gpu_median,
+            gpu_p95
+        );
+    }
+
+    text
+}
+
+/// One row of statistics per shader
+fn summary_csv(runs: &[(&Path, BenchResults)]) -> String {
+    let mut csv = String::from("shader,frames");
+
+    for source in ["cpu", "gpu"] {
+        for stat in ["mean", "median", "p95", "p99", "min", "max"] {
+            let _ = write!(csv, ",{}_{}_ms", source, stat);
+        }
+    }
+
+    csv.push('\n');
+
+    for (shader, results) in runs {
+        let _ = write!(csv, "{},{}", csv_field(&shader.to_string_lossy()), results.cpu_times.len());
+
+        for stats in [Some(results.cpu_stats()), results.gpu_stats()] {
+            match stats {
+                Some(s) => {
+                    let _ = write!(
+                        csv,
+                        ",{},{},{},{},{},{}",
+                        s.mean, s.median, s.p95, s.p99, s.min, s.max
+                    );
+                }
+                None => csv.push_str(",,,,,,"),
+            }
+        }
+
+        csv.push('\n');
+    }
+
+    csv
+}
+
+fn write_row(text: &mut String, label: &str, stats: &Stats) {
+    let _ = writeln!(
+        text,
+        "{:<16} {:>8.3} {:>8.3} {:>8.3} {:>8.3} {:>8.3} {:>8.3}",
+        label, stats.mean, stats.median, stats.p95, stats.p99, stats.min, stats.max
+    );
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", u32::from(c));
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
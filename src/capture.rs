@@ -0,0 +1,43 @@
+use glfw_sys::*;
+
+use crate::date::DateTime;
+
+use std::path::{Path, PathBuf};
+
+/// Writes tightly packed RGBA8 pixels to a PNG file. Alpha is dropped, like it is when presenting.
+pub fn save_png(path: &Path, rgba: &[u8], width: u32, height: u32) -> Result<(), String> {
+    let rgb: Vec<u8> = rgba.chunks_exact(4).flat_map(|pixel| &pixel[..3]).copied().collect();
+
+    image::save_buffer(path, &rgb, width, height, image::ColorType::Rgb8)
+        .map_err(|e| format!("failed to save {}: {}", path.display(), e))
+}
+
+/// Reorders pixels copied from a swapchain image into RGBA8.
+///
+/// No transfer function is applied: both sRGB and UNORM swapchain images hold the values that are
+/// sent to the display, which is also how PNG viewers interpret them.
+pub fn swapchain_to_rgba8(pixels: &mut [u8], format: VkFormat) -> Result<(), String> {
+    match format {
+        VK_FORMAT_R8G8B8A8_SRGB | VK_FORMAT_R8G8B8A8_UNORM => Ok(()),
+        VK_FORMAT_B8G8R8A8_SRGB | VK_FORMAT_B8G8R8A8_UNORM => {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+
+            Ok(())
+        }
+        _ => Err(format!("unsupported swapchain format for screenshots: {}", format)),
+    }
+}
+
+/// Returns a file name in the current directory based on the current UTC time
+pub fn screenshot_path() -> PathBuf {
+    let now = DateTime::now_utc();
+
+    let name = format!(
+        "screenshot-{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}.png",
+        now.year, now.month, now.day, now.hour, now.minute, now.second, now.millisecond
+    );
+
+    PathBuf::from(name)
+}
@@ -0,0 +1,144 @@
+use crate::window::{Key, Modifiers};
+
+/// Speeds that the speed keys go through
+const SPEEDS: [f64; 7] = [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0];
+
+pub const MIN_SPEED: f64 = SPEEDS[0];
+pub const MAX_SPEED: f64 = SPEEDS[SPEEDS.len() - 1];
+
+/// Seconds skipped by Shift+Left and Shift+Right
+const SKIP_SECONDS: f64 = 1.0;
+
+/// Time seen by shaders, which can be paused, sped up or reversed independently of the wall clock.
+/// It never goes below zero.
+pub struct Clock {
+    time: f64,
+    /// Always positive, direction is kept separately
+    speed: f64,
+    reversed: bool,
+    paused: bool,
+    /// Amount of time in a frame step
+    step: f64,
+    /// Time typed so far, jumped to on Enter
+    jump_input: Option<String>,
+}
+
+impl Clock {
+    /// Negative `speed` plays in reverse
+    pub fn new(time: f64, speed: f64, paused: bool, step: f64) -> Self {
+        Self {
+            time: time.max(0.0),
+            speed: speed.abs(),
+            reversed: speed < 0.0,
+            paused,
+            step,
+            jump_input: None,
+        }
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Moves time forward by `dt` of wall clock time, scaled by the speed
+    pub fn advance(&mut self, dt: f64) {
+        if self.paused {
+            return;
+        }
+
+        let direction = if self.reversed { -1.0 } else { 1.0 };
+
+        self.jump_to(self.time + dt * self.speed * direction);
+    }
+
+    /// Returns whether the key was used
+    pub fn handle_key(&mut self, key: Key, mods: Modifiers) -> bool {
+        match key {
+            Key::Space => self.paused = !self.paused,
+            Key::Up => self.speed = next_speed(self.speed),
+            Key::Down => self.speed = previous_speed(self.speed),
+            Key::R => self.reversed = !self.reversed,
+            Key::Right if mods.shift() => self.jump_to(self.time + SKIP_SECONDS),
+            Key::Left if mods.shift() => self.jump_to(self.time - SKIP_SECONDS),
+            Key::Right => self.step_by(1.0),
+            Key::Left => self.step_by(-1.0),
+            Key::Home => self.jump_to(0.0),
+            _ => return self.handle_jump_key(key, mods),
+        }
+
+        true
+    }
+
+    /// Keys used while typing a time to jump to
+    fn handle_jump_key(&mut self, key: Key, mods: Modifiers) -> bool {
+        let Some(input) = &mut self.jump_input else {
+            return false;
+        };
+
+        match key {
+            Key::Enter if !mods.alt() => {
+                let input = self.jump_input.take().unwrap_or_default();
+
+                match input.parse() {
+                    Ok(time) => self.jump_to(time),
+                    Err(_) => eprintln!("Error: invalid time \"{}\"", input),
+                }
+            }
+            Key::Backspace => {
+                input.pop();
+            }
+            Key::Escape => self.jump_input = None,
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Digits and the decimal point start or continue typing a time to jump to
+    pub fn handle_char(&mut self, ch: char) {
+        if ch.is_ascii_digit() || ch == '.' {
+            self.jump_input.get_or_insert_with(String::new).push(ch);
+        }
+    }
+
+    /// Current time followed by whatever differs from normal playback
+    pub fn status(&self) -> String {
+        let mut parts = vec![format!("t = {:.2} s", self.time)];
+
+        if self.paused {
+            parts.push("paused".to_string());
+        }
+
+        if self.reversed {
+            parts.push("reversed".to_string());
+        }
+
+        if (self.speed - 1.0).abs() > f64::EPSILON {
+            parts.push(format!("{}x", self.speed));
+        }
+
+        if let Some(input) = &self.jump_input {
+            parts.push(format!("jump to {}_", input));
+        }
+
+        parts.join(", ")
+    }
+
+    /// Pauses, then moves by `frames` frames
+    fn step_by(&mut self, frames: f64) {
+        self.paused = true;
+        self.jump_to(self.time + frames * self.step);
+    }
+
+    fn jump_to(&mut self, time: f64) {
+        self.time = time.max(0.0);
+    }
+}
+
+fn next_speed(speed: f64) -> f64 {
+    SPEEDS.into_iter().find(|&s| s > speed).unwrap_or(MAX_SPEED)
+}
+
+fn previous_speed(speed: f64) -> f64 {
+    SPEEDS.into_iter().rev().find(|&s| s < speed).unwrap_or(MIN_SPEED)
+}
@@ -0,0 +1,356 @@
+use glfw_sys::*;
+use serde::{Deserialize, Serialize};
+
+use crate::args::Args;
+use crate::shaders;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Looked up in the user's configuration directory and in the current directory
+pub const CONFIG_NAME: &str = "vxtr.toml";
+
+/// Tried in order, so the first one is only a preference
+const DEFAULT_PRESENT_MODES: [PresentMode; 4] = [
+    PresentMode::Immediate,
+    PresentMode::FifoRelaxed,
+    PresentMode::Mailbox,
+    PresentMode::Fifo,
+];
+
+/// Layer that `--validation` enables
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+/// Settings read from vxtr.toml files. Later sources override earlier ones: built-in defaults,
+/// the user's file, the one in the current directory, the one given with `--config`, and finally
+/// command line options.
+#[derive(Serialize)]
+pub struct Config {
+    pub window: WindowConfig,
+    pub render: RenderConfig,
+    pub shaders: ShaderConfig,
+    /// Files the settings were loaded from, in order
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct WindowConfig {
+    pub width: u32,
+    pub height: u32,
+    /// Seconds between updates of the timings in the title
+    pub title_update_interval: f64,
+    /// Index of the monitor used for fullscreen, the primary one if not set
+    pub monitor: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct RenderConfig {
+    /// Rate at which time advances between frames
+    pub updates_per_second: u32,
+    pub frames_in_flight: usize,
+    /// The first one that the device supports is used
+    pub present_modes: Vec<PresentMode>,
+    /// Index, UUID or part of the name of the GPU, chosen automatically if not set
+    pub device: Option<String>,
+    pub validation: ValidationConfig,
+}
+
+/// Debugging aid, slows rendering down
+#[derive(Serialize)]
+pub struct ValidationConfig {
+    /// Vulkan layers to enable, skipped with a warning when not installed
+    pub layers: Vec<String>,
+    /// Also print info and verbose messages, not only warnings and errors
+    pub verbose: bool,
+    /// Make the Vulkan call that a validation error is reported for fail
+    pub fail_on_error: bool,
+}
+
+#[derive(Serialize)]
+pub struct ShaderConfig {
+    /// Run when no shader is given
+    pub default: PathBuf,
+    /// Seconds between checks for changed shader files
+    pub reload_check_interval: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentMode {
+    Immediate,
+    Mailbox,
+    Fifo,
+    FifoRelaxed,
+}
+
+/// Same layout as `Config`, with everything optional
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    window: WindowFile,
+    render: RenderFile,
+    shaders: ShaderFile,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WindowFile {
+    width: Option<u32>,
+    height: Option<u32>,
+    title_update_interval: Option<f64>,
+    monitor: Option<usize>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RenderFile {
+    updates_per_second: Option<u32>,
+    frames_in_flight: Option<usize>,
+    present_modes: Option<Vec<PresentMode>>,
+    device: Option<String>,
+    validation: ValidationFile,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ValidationFile {
+    layers: Option<Vec<String>>,
+    verbose: Option<bool>,
+    fail_on_error: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ShaderFile {
+    default: Option<PathBuf>,
+    reload_check_interval: Option<f64>,
+}
+
+impl Config {
+    /// Reads the configuration files that exist and applies the options given in `args`
+    pub fn load(args: &Args) -> Result<Self, String> {
+        let mut config = Self::default();
+
+        let mut paths: Vec<PathBuf> = user_config_path().into_iter().collect();
+        paths.push(PathBuf::from(CONFIG_NAME));
+
+        for path in paths {
+            if path.is_file() {
+                config.load_file(&path)?;
+            }
+        }
+
+        if let Some(path) = &args.config {
+            config.load_file(path)?;
+        }
+
+        config.apply_args(args);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Lists the loaded files, followed by the resulting settings in the same format
+    pub fn print(&self) {
+        if self.files.is_empty() {
+            println!("# No configuration files found, using defaults");
+        }
+
+        for path in &self.files {
+            println!("# Loaded {}", path.display());
+        }
+
+        match toml::to_string_pretty(self) {
+            Ok(contents) => print!("{}", contents),
+            Err(e) => eprintln!("Error: failed to serialize configuration: {}", e),
+        }
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+        let file: ConfigFile = toml::from_str(&contents)
+            .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+
+        let ConfigFile {
+            window,
+            render,
+            shaders,
+        } = file;
+
+        set(&mut self.window.width, window.width);
+        set(&mut self.window.height, window.height);
+        set(&mut self.window.title_update_interval, window.title_update_interval);
+        set(&mut self.window.monitor, window.monitor.map(Some));
+
+        set(&mut self.render.updates_per_second, render.updates_per_second);
+        set(&mut self.render.frames_in_flight, render.frames_in_flight);
+        set(&mut self.render.present_modes, render.present_modes);
+        set(&mut self.render.device, render.device.map(Some));
+        set(&mut self.render.validation.layers, render.validation.layers);
+        set(&mut self.render.validation.verbose, render.validation.verbose);
+        set(&mut self.render.validation.fail_on_error, render.validation.fail_on_error);
+
+        // Relative to the file, like paths in project manifests
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        set(&mut self.shaders.default, shaders.default.map(|default| dir.join(default)));
+        set(&mut self.shaders.reload_check_interval, shaders.reload_check_interval);
+
+        self.files.push(path.to_path_buf());
+
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some((width, height)) = args.size {
+            self.window.width = width;
+            self.window.height = height;
+        }
+
+        set(&mut self.window.monitor, args.monitor.map(Some));
+        set(&mut self.render.updates_per_second, args.updates_per_second);
+        set(&mut self.render.frames_in_flight, args.frames_in_flight);
+
+        if let Some(mode) = args.present_mode {
+            self.render.present_modes = vec![mode];
+        }
+
+        set(&mut self.render.device, args.device.clone().map(Some));
+
+        let layers = &mut self.render.validation.layers;
+
+        if args.validation && !layers.iter().any(|layer| layer == VALIDATION_LAYER) {
+            layers.push(VALIDATION_LAYER.to_string());
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.window.width == 0 || self.window.height == 0 {
+            return Err("window size can't be zero".to_string());
+        }
+
+        // GLFW takes window sizes as i32
+        if i32::try_from(self.window.width.max(self.window.height)).is_err() {
+            return Err(format!("window size can't be larger than {}", i32::MAX));
+        }
+
+        let title_interval = self.window.title_update_interval;
+
+        if !title_interval.is_finite() || title_interval < 0.0 {
+            return Err(format!("invalid title update interval {}", title_interval));
+        }
+
+        let reload_interval = self.shaders.reload_check_interval;
+
+        if !reload_interval.is_finite() || reload_interval < 0.0 {
+            return Err(format!("invalid reload check interval {}", reload_interval));
+        }
+
+        if self.render.updates_per_second == 0 {
+            return Err("updates per second can't be zero".to_string());
+        }
+
+        if self.render.frames_in_flight == 0 {
+            return Err("at least one frame has to be in flight".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            window: WindowConfig {
+                width: 800,
+                height: 600,
+                title_update_interval: 0.03,
+                monitor: None,
+            },
+            render: RenderConfig {
+                updates_per_second: 60,
+                frames_in_flight: 2,
+                present_modes: DEFAULT_PRESENT_MODES.to_vec(),
+                device: None,
+                validation: ValidationConfig {
+                    layers: Vec::new(),
+                    verbose: false,
+                    fail_on_error: false,
+                },
+            },
+            shaders: ShaderConfig {
+                default: PathBuf::from(shaders::DEFAULT_SHADER),
+                reload_check_interval: 0.25,
+            },
+            files: Vec::new(),
+        }
+    }
+}
+
+impl RenderConfig {
+    pub fn vk_present_modes(&self) -> Vec<VkPresentModeKHR> {
+        self.present_modes.iter().map(|mode| mode.to_vk()).collect()
+    }
+
+    /// The first present mode, if the modes were set rather than left at the defaults
+    pub fn requested_present_mode(&self) -> Option<PresentMode> {
+        if self.present_modes[..] == DEFAULT_PRESENT_MODES {
+            None
+        } else {
+            self.present_modes.first().copied()
+        }
+    }
+
+    pub fn device_selector(&self) -> Option<vk::DeviceSelector> {
+        self.device.as_deref().map(vk::DeviceSelector::parse)
+    }
+
+    pub fn vk_validation(&self) -> vk::Validation {
+        vk::Validation {
+            layers: self.validation.layers.clone(),
+            verbose: self.validation.verbose,
+            fail_on_error: self.validation.fail_on_error,
+        }
+    }
+}
+
+impl PresentMode {
+    /// Same names as in configuration files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "immediate" => Some(PresentMode::Immediate),
+            "mailbox" => Some(PresentMode::Mailbox),
+            "fifo" => Some(PresentMode::Fifo),
+            "fifo_relaxed" => Some(PresentMode::FifoRelaxed),
+            _ => None,
+        }
+    }
+
+    pub fn to_vk(self) -> VkPresentModeKHR {
+        match self {
+            PresentMode::Immediate => VK_PRESENT_MODE_IMMEDIATE_KHR,
+            PresentMode::Mailbox => VK_PRESENT_MODE_MAILBOX_KHR,
+            PresentMode::Fifo => VK_PRESENT_MODE_FIFO_KHR,
+            PresentMode::FifoRelaxed => VK_PRESENT_MODE_FIFO_RELAXED_KHR,
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/vxtr/vxtr.toml`, or `~/.config/vxtr/vxtr.toml`
+fn user_config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+    Some(config_dir.join("vxtr").join(CONFIG_NAME))
+}
+
+fn set<T>(value: &mut T, new_value: Option<T>) {
+    if let Some(new_value) = new_value {
+        *value = new_value;
+    }
+}
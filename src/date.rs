@@ -0,0 +1,65 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Calendar date and time of day in UTC
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+}
+
+impl DateTime {
+    pub fn now_utc() -> Self {
+        Self::from_unix(unix_now())
+    }
+
+    pub fn from_unix(since_epoch: Duration) -> Self {
+        let secs = since_epoch.as_secs();
+        let days = i64::try_from(secs / 86400).unwrap();
+        let secs_of_day = u32::try_from(secs % 86400).unwrap();
+
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3600,
+            minute: secs_of_day / 60 % 60,
+            second: secs_of_day % 60,
+            millisecond: since_epoch.subsec_millis(),
+        }
+    }
+
+    pub fn seconds_of_day(&self) -> f64 {
+        f64::from(self.hour * 3600 + self.minute * 60 + self.second)
+            + f64::from(self.millisecond) / 1000.0
+    }
+}
+
+/// Time elapsed since the Unix epoch
+pub fn unix_now() -> Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::ZERO)
+}
+
+/// Converts days since 1970-01-01 to (year, month, day) in the proleptic Gregorian calendar.
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+
+    let day = u32::try_from(day_of_year - (153 * month_index + 2) / 5 + 1).unwrap();
+    let month = u32::try_from(month_index).unwrap();
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
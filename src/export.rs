@@ -0,0 +1,71 @@
+use crate::args::ExportArgs;
+use crate::capture;
+use crate::config::RenderConfig;
+use crate::project::CompiledProject;
+use crate::state::State;
+use crate::y4m::Y4mWriter;
+
+use std::fs;
+use std::io::{self, BufWriter, StdoutLock};
+use std::path::PathBuf;
+
+enum Output {
+    Png(PathBuf),
+    Y4m(Y4mWriter<BufWriter<StdoutLock<'static>>>),
+}
+
+/// Renders frames offscreen with a fixed time step, so the output doesn't depend on how long
+/// rendering takes
+pub fn export(
+    project: CompiledProject,
+    args: &ExportArgs,
+    config: &RenderConfig,
+) -> Result<(), String> {
+    let mut output = if args.y4m {
+        let stdout = BufWriter::new(io::stdout().lock());
+        let writer = Y4mWriter::new(stdout, args.width, args.height, args.fps)
+            .map_err(|e| format!("failed to write Y4M header: {}", e))?;
+
+        Output::Y4m(writer)
+    } else {
+        fs::create_dir_all(&args.out)
+            .map_err(|e| format!("failed to create {}: {}", args.out.display(), e))?;
+
+        Output::Png(args.out.clone())
+    };
+
+    let mut state = State::new_offscreen(args.width, args.height, project, config)?;
+
+    let dt = 1.0 / f64::from(args.fps);
+    let frame_count = args.frame_count();
+
+    for frame in 0..frame_count {
+        // Computed from the frame number rather than accumulated, so that there is no drift
+        #[allow(clippy::cast_precision_loss)]
+        let time = frame as f64 * dt;
+
+        state.update(dt, time);
+
+        let pixels = state.render_to_image()?;
+
+        match &mut output {
+            Output::Png(dir) => {
+                let path = dir.join(format!("{:05}.png", frame));
+                capture::save_png(&path, &pixels, args.width, args.height)?;
+            }
+            Output::Y4m(writer) => writer
+                .write_frame(&pixels)
+                .map_err(|e| format!("failed to write frame {}: {}", frame, e))?,
+        }
+
+        eprint!("\rExported frame {}/{}", frame + 1, frame_count);
+    }
+
+    eprintln!();
+
+    if let Output::Y4m(writer) = output {
+        writer.finish().map_err(|e| format!("failed to flush Y4M stream: {}", e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use glfw_sys::*;
+
+/// Measures how long each pass takes on the GPU with timestamp queries. Every frame in flight has
+/// its own query pool, whose results are read once that frame's fence is signaled, so timings lag
+/// a frame or two behind.
+pub struct GpuTimer {
+    pools: Vec<vk::QueryPool>,
+    /// Whether the pool was written by a submitted frame, as unwritten queries can't be read
+    written: Vec<bool>,
+    /// Milliseconds per tick
+    period: f64,
+    mask: u64,
+}
+
+impl GpuTimer {
+    /// Creates a timer for `interval_count` consecutive intervals, or returns `None` if the
+    /// graphics queue doesn't support timestamps
+    pub fn new(
+        device: &vk::Device,
+        frames_in_flight: usize,
+        interval_count: usize,
+    ) -> Result<Option<Self>, String> {
+        let bits = device.graphics_timestamp_bits();
+
+        if bits == 0 {
+            return Ok(None);
+        }
+
+        let query_count = u32::try_from(interval_count + 1).unwrap();
+        let pools = (0..frames_in_flight)
+            .map(|_| device.create_timestamp_query_pool(query_count))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(Self {
+            pools,
+            written: vec![false; frames_in_flight],
+            period: f64::from(device.timestamp_period()) / 1_000_000.0,
+            mask: u64::MAX >> (64 - bits.min(64)),
+        }))
+    }
+
+    /// Must be recorded outside of a render pass, before any `end_interval`
+    pub fn begin(&mut self, handle: &vk::CommandBufferRecording, frame: usize) {
+        let pool = &self.pools[frame];
+
+        handle.reset_query_pool(pool);
+        handle.write_timestamp(VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT, pool, 0);
+
+        self.written[frame] = true;
+    }
+
+    /// Marks the end of interval `index` and the start of the next one, once all commands recorded
+    /// so far are done
+    pub fn end_interval(&self, handle: &vk::CommandBufferRecording, frame: usize, index: usize) {
+        let query = u32::try_from(index + 1).unwrap();
+
+        handle.write_timestamp(VK_PIPELINE_STAGE_BOTTOM_OF_PIPE_BIT, &self.pools[frame], query);
+    }
+
+    /// Returns the duration of each interval in milliseconds, if the GPU has finished `frame`.
+    /// Each written frame is only returned once.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn read(&mut self, frame: usize) -> Result<Option<Vec<f64>>, String> {
+        if !self.written[frame] {
+            return Ok(None);
+        }
+
+        let Some(timestamps) = self.pools[frame].get_results()? else {
+            return Ok(None);
+        };
+
+        self.written[frame] = false;
+
+        let durations = timestamps
+            .windows(2)
+            .map(|pair| (pair[1].wrapping_sub(pair[0]) & self.mask) as f64 * self.period)
+            .collect();
+
+        Ok(Some(durations))
+    }
+}
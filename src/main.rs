@@ -1,33 +1,124 @@
 #![allow(clippy::wildcard_imports, clippy::uninlined_format_args)]
 
-use state::State;
+use args::{Args, BenchArgs, Command, RenderArgs};
+use clock::Clock;
+use config::{Config, RenderConfig};
+use params::Presets;
+use project::{CompiledProject, Project};
+use state::{Screenshot, State};
 use window::{Resolution, Window};
 
-use crate::window::{Event, Key};
-
+use crate::watcher::ShaderWatcher;
+use crate::window::{Event, Key, MouseButton};
+
+use std::path::{Path, PathBuf};
+
+mod args;
+mod baseline;
+mod bench;
+mod capture;
+mod clock;
+mod config;
+mod date;
+mod export;
+mod gpu_timer;
+mod params;
+mod passes;
+mod project;
+mod shaders;
+mod shadertoy;
 mod state;
+mod watcher;
 mod window;
+mod y4m;
 
 fn main() {
-    let mut window = Window::new(Resolution::Windowed(800, 600), "vxtr");
-    window.set_callbacks();
+    let args = Args::parse();
+
+    let config = Config::load(&args).unwrap_or_else(|message| {
+        eprintln!("Error: {}", message);
+        std::process::exit(1);
+    });
 
-    let mut state = State::new(window.as_inner());
+    if args.print_config {
+        config.print();
+        return;
+    }
 
-    if is_benchmark_mode() {
-        benchmark(window, state);
+    if args.list {
+        list_shaders();
         return;
     }
 
-    let updates_per_second: i16 = 60;
-    let dt = 1.0 / f64::from(updates_per_second);
+    let shader_path = args.shader.clone().unwrap_or_else(|| config.shaders.default.clone());
+
+    if let Command::Bench(bench_args) = &args.command {
+        run_bench(bench_args, shader_path, &config);
+        return;
+    }
+
+    let project = load_project_or_exit(&shader_path);
+    let compiled_project = compile_project_or_exit(&project);
+
+    match &args.command {
+        Command::Run => (),
+        Command::Render(render_args) => {
+            exit_on_error(render_frame(compiled_project, render_args, &config.render));
+            return;
+        }
+        Command::Export(export_args) => {
+            exit_on_error(export::export(compiled_project, export_args, &config.render));
+            return;
+        }
+        // Loads its own projects
+        Command::Bench(_) => unreachable!(),
+    }
+
+    let dt = 1.0 / f64::from(config.render.updates_per_second);
+    let clock = Clock::new(args.start_time, args.speed.unwrap_or(1.0), args.paused, dt);
+    let presets = Presets::new(project.presets_path(), args.preset.clone());
+
+    exit_on_error(run(&project, compiled_project, &shader_path, &config, clock, &presets));
+}
+
+/// Renders in a window until it's closed, reloading the project when its files change
+fn run(
+    project: &Project,
+    compiled_project: CompiledProject,
+    shader_path: &Path,
+    config: &Config,
+    mut clock: Clock,
+    presets: &Presets,
+) -> Result<(), String> {
+    // Both fit, `Config::validate` checks
+    let width = config.window.width.try_into().unwrap();
+    let height = config.window.height.try_into().unwrap();
+
+    let mut window =
+        Window::new(Resolution::Windowed(width, height), config.window.monitor, true, "vxtr");
+    window.set_callbacks();
+
+    let mut state = State::new(window.as_inner(), compiled_project, &config.render)?;
+
+    // Events only come once the cursor moves, but a click can come before that
+    let (cursor_x, cursor_y) = window.cursor_pos();
+    let (scale_x, scale_y) = window.framebuffer_scale();
+    state.handle_cursor_move(cursor_x * scale_x, cursor_y * scale_y);
+
+    report_error(presets.apply_named(state.params_mut()));
+
+    let dt = 1.0 / f64::from(config.render.updates_per_second);
 
     let mut current_time = Window::current_time();
     let mut minimized = false;
 
-    let title_update_delay = 0.03;
+    let title_update_delay = config.window.title_update_interval;
     let mut next_title_update_time = 0.0;
 
+    let mut watcher = ShaderWatcher::new(project.files());
+    let reload_check_delay = config.shaders.reload_check_interval;
+    let mut next_reload_check_time = 0.0;
+
     'main_loop: while window.running {
         if minimized {
             Window::block_until_event();
@@ -37,13 +128,36 @@ fn main() {
 
         while current_time < real_time {
             current_time += dt;
-            state.update(dt, current_time);
+            clock.advance(dt);
         }
 
+        let resolution = window.resolution();
+        let windowed_size = window.windowed_size();
+        let (scale_x, scale_y) = window.framebuffer_scale();
+        let mut new_resolution = None;
+
         for event in window.poll_events() {
             match event {
-                Event::KeyPress(Key::Escape) => break 'main_loop,
-                Event::WindowResize(width, height) => {
+                // Before the other keys, so that Escape cancels typing a time instead of quitting
+                Event::KeyPress(key, _, mods) if clock.handle_key(key, mods) => (),
+                Event::Char(ch) => clock.handle_char(ch),
+                Event::KeyPress(Key::Escape, _, _) => break 'main_loop,
+                Event::KeyPress(Key::F11, _, _) => {
+                    new_resolution = Some(cycle_resolution(resolution, windowed_size));
+                }
+                Event::KeyPress(Key::Enter, _, mods) if mods.alt() => {
+                    new_resolution = Some(toggle_fullscreen(resolution, windowed_size));
+                }
+                Event::KeyPress(Key::V, _, _) => {
+                    let present_mode = state.toggle_vsync()?;
+                    eprintln!("Present mode: {}", present_mode);
+                }
+                Event::KeyPress(Key::F12, _, _) => report_error(state.request_screenshot()),
+                Event::KeyPress(key, _, mods) | Event::KeyRepeat(key, _, mods)
+                    if state.params_mut().handle_key(key, mods) => {}
+                Event::KeyPress(Key::F5, _, _) => report_error(presets.save(state.params())),
+                Event::KeyPress(Key::F9, _, _) => report_error(presets.load(state.params_mut())),
+                Event::FramebufferResize(width, height) => {
                     if width == 0 || height == 0 {
                         minimized = true;
                         continue 'main_loop;
@@ -51,15 +165,40 @@ fn main() {
 
                     minimized = false;
 
-                    state.handle_resize(width, height);
+                    state.handle_resize(width, height)?;
                 }
+                Event::CursorMove(x, y) => state.handle_cursor_move(x * scale_x, y * scale_y),
+                Event::MousePress(MouseButton::Left) => state.handle_mouse_press(),
+                Event::MouseRelease(MouseButton::Left) => state.handle_mouse_release(),
                 _ => (),
             }
         }
 
+        if let Some(resolution) = new_resolution {
+            window.set_resolution(resolution);
+            state.handle_fullscreen_switch()?;
+        }
+
+        // After events, so that jumps and steps show up in this frame
+        state.update(dt, clock.time());
+
+        if real_time > next_reload_check_time {
+            next_reload_check_time = real_time + reload_check_delay;
+
+            if watcher.poll() {
+                if let Some(files) = reload_project(&mut state, shader_path) {
+                    watcher = ShaderWatcher::new(files);
+                }
+            }
+        }
+
         let draw_start = Window::current_time();
 
-        state.present();
+        state.present()?;
+
+        if let Some(screenshot) = state.take_screenshot() {
+            save_screenshot(screenshot);
+        }
 
         let frame_end = Window::current_time();
 
@@ -69,64 +208,167 @@ fn main() {
             let draw_time = frame_end - draw_start;
             let frame_time = frame_end - real_time;
 
-            let draw_ms = draw_time * 1000.0;
-            let fps = 1.0 / frame_time;
+            window.set_title(format_title(&state, &clock, draw_time, frame_time));
+        }
+    }
 
-            let title = format!("vxtr | draw = {:05.2} ms, FPS = {:04.0}", draw_ms, fps);
+    Ok(())
+}
 
-            window.set_title(title);
-        }
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(message) = result {
+        eprintln!("Error: {}", message);
+        std::process::exit(1);
     }
 }
 
-fn is_benchmark_mode() -> bool {
-    let mut args = std::env::args();
+/// For errors that don't stop the window, like failing to save a screenshot
+fn report_error(result: Result<(), String>) {
+    if let Err(message) = result {
+        eprintln!("Error: {}", message);
+    }
+}
+
+fn format_title(state: &State, clock: &Clock, draw_time: f64, frame_time: f64) -> String {
+    let fps = 1.0 / frame_time;
 
-    args.any(|arg| matches!(arg.as_str(), "--benchmark" | "-b"))
+    let timings = match state.gpu_times() {
+        Some(times) => format_gpu_times(&times),
+        // Measured on the CPU, so includes waiting for the GPU and presentation
+        None => format!("draw = {:05.2} ms", draw_time * 1000.0),
+    };
+
+    format!("vxtr | {} | {}, FPS = {:04.0}", clock.status(), timings, fps)
 }
 
-fn benchmark(window: Window, state: State) {
-    let frames = 5_000;
+/// Total GPU time, followed by the time of each pass if there are several
+fn format_gpu_times(times: &[(&str, f64)]) -> String {
+    let total: f64 = times.iter().map(|(_, ms)| ms).sum();
 
-    match get_time_rendering_n_frames(window, state, frames) {
-        Some(seconds) => println!("Rendered {} frames in {} seconds", frames, seconds),
-        None => println!("Error"),
+    if times.len() == 1 {
+        return format!("gpu = {:05.2} ms", total);
     }
-}
 
-fn get_time_rendering_n_frames(mut window: Window, mut state: State, n: usize) -> Option<f64> {
-    let start_time = Window::current_time();
+    let passes: Vec<String> =
+        times.iter().map(|(name, ms)| format!("{} {:05.2}", name, ms)).collect();
 
-    let updates_per_second: i16 = 60;
-    let dt = 1.0 / f64::from(updates_per_second);
+    format!("gpu = {:05.2} ms ({})", total, passes.join(", "))
+}
 
-    let mut current_frame = 0;
-    let mut current_time = Window::current_time();
+fn run_bench(args: &BenchArgs, shader_path: PathBuf, config: &Config) {
+    let shaders = if args.shaders.is_empty() {
+        vec![shader_path]
+    } else {
+        args.shaders.clone()
+    };
 
-    while window.running {
-        current_frame += 1;
+    exit_on_error(bench::bench(&shaders, args, &config.render));
+}
 
-        if current_frame > n {
-            break;
+fn list_shaders() {
+    match shaders::list(Path::new(shaders::SHADER_DIR)) {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
         }
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            std::process::exit(1);
+        }
+    }
+}
 
-        let real_time = Window::current_time();
+fn load_project_or_exit(path: &Path) -> Project {
+    match Project::load(path) {
+        Ok(project) => project,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            std::process::exit(1);
+        }
+    }
+}
 
-        while current_time < real_time {
-            current_time += dt;
-            state.update(dt, current_time);
+fn compile_project_or_exit(project: &Project) -> CompiledProject {
+    match project.compile() {
+        Ok(compiled) => compiled,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            std::process::exit(1);
         }
+    }
+}
 
-        for event in window.poll_events() {
-            if matches!(event, Event::WindowResize(_, _)) {
-                return None;
-            }
+/// Returns the files of the reloaded project, which may have changed along with the manifest.
+/// Errors are reported and leave the old project running.
+fn reload_project(state: &mut State, path: &Path) -> Option<Vec<PathBuf>> {
+    let result = Project::load(path).and_then(|project| {
+        let compiled = project.compile()?;
+        state.reload_project(compiled)?;
+
+        Ok(project.files())
+    });
+
+    match result {
+        Ok(files) => {
+            println!("Reloaded {}", path.display());
+            Some(files)
         }
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            None
+        }
+    }
+}
+
+fn render_frame(
+    compiled_project: CompiledProject,
+    args: &RenderArgs,
+    config: &RenderConfig,
+) -> Result<(), String> {
+    let mut state = State::new_offscreen(args.width, args.height, compiled_project, config)?;
+
+    state.update(0.0, args.time);
+
+    let pixels = state.render_to_image()?;
+
+    capture::save_png(&args.out, &pixels, args.width, args.height)?;
+    println!("Saved {}", args.out.display());
 
-        state.present();
+    Ok(())
+}
+
+fn save_screenshot(mut screenshot: Screenshot) {
+    let path = capture::screenshot_path();
+
+    let result =
+        capture::swapchain_to_rgba8(&mut screenshot.pixels, screenshot.format).and_then(|()| {
+            capture::save_png(&path, &screenshot.pixels, screenshot.width, screenshot.height)
+        });
+
+    match result {
+        Ok(()) => println!("Saved {}", path.display()),
+        Err(message) => eprintln!("Error: {}", message),
+    }
+}
+
+/// Windowed, then borderless fullscreen, then exclusive fullscreen at the size of the window
+fn cycle_resolution(resolution: Resolution, windowed_size: (i32, i32)) -> Resolution {
+    let (width, height) = windowed_size;
+
+    match resolution {
+        Resolution::Windowed(..) => Resolution::Fullscreen,
+        Resolution::Fullscreen => Resolution::FullscreenWithRes(width, height),
+        Resolution::FullscreenWithRes(..) => Resolution::Windowed(width, height),
     }
+}
 
-    let elapsed = Window::current_time() - start_time;
+/// Between windowed and borderless fullscreen
+fn toggle_fullscreen(resolution: Resolution, windowed_size: (i32, i32)) -> Resolution {
+    let (width, height) = windowed_size;
 
-    Some(elapsed)
+    match resolution {
+        Resolution::Windowed(..) => Resolution::Fullscreen,
+        _ => Resolution::Windowed(width, height),
+    }
 }
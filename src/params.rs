@@ -0,0 +1,499 @@
+use serde::{Deserialize, Serialize};
+
+use crate::window::{Key, Modifiers};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Binding of the uniform buffer that holds parameter values, after the channels and the output
+/// image of compute passes
+pub const PARAMS_BINDING: u32 = 5;
+
+/// Marks a parameter declaration in a comment
+const DECLARATION_PREFIX: &str = "@param";
+
+/// Fraction of the range that a parameter changes by per key press, or ten times that with Shift
+const STEP_FRACTION: f32 = 0.01;
+
+const COMPONENT_NAMES: [char; 4] = ['x', 'y', 'z', 'w'];
+
+/// Preset used when none is named
+const DEFAULT_PRESET: &str = "default";
+
+/// A value that can be tweaked while the shader runs, declared in its source with a comment like
+/// `// @param vec3 light = (0, 2, 0) [-10, 10]`. The range applies to every component.
+#[derive(Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub ty: ParamType,
+    pub default: [f32; 4],
+    pub min: f32,
+    pub max: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    Float,
+    Int,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+/// Current values of a project's parameters, along with the component that keys change
+pub struct ParamValues {
+    params: Vec<Param>,
+    values: Vec<[f32; 4]>,
+    selected: usize,
+    component: usize,
+}
+
+/// Named sets of parameter values, kept in one TOML file per shader or project
+pub struct Presets {
+    path: PathBuf,
+    name: String,
+    /// Whether the preset was asked for and should be applied on start
+    named: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PresetValue {
+    Scalar(f32),
+    Vector(Vec<f32>),
+}
+
+type PresetFile = BTreeMap<String, BTreeMap<String, PresetValue>>;
+
+impl ParamType {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "float" => Some(ParamType::Float),
+            "int" => Some(ParamType::Int),
+            "vec2" => Some(ParamType::Vec2),
+            "vec3" => Some(ParamType::Vec3),
+            "vec4" => Some(ParamType::Vec4),
+            _ => None,
+        }
+    }
+
+    fn glsl_name(self) -> &'static str {
+        match self {
+            ParamType::Float => "float",
+            ParamType::Int => "int",
+            ParamType::Vec2 => "vec2",
+            ParamType::Vec3 => "vec3",
+            ParamType::Vec4 => "vec4",
+        }
+    }
+
+    fn components(self) -> usize {
+        match self {
+            ParamType::Float | ParamType::Int => 1,
+            ParamType::Vec2 => 2,
+            ParamType::Vec3 => 3,
+            ParamType::Vec4 => 4,
+        }
+    }
+
+    /// Base alignment under the std140 layout
+    fn alignment(self) -> usize {
+        match self {
+            ParamType::Float | ParamType::Int => 4,
+            ParamType::Vec2 => 8,
+            ParamType::Vec3 | ParamType::Vec4 => 16,
+        }
+    }
+}
+
+/// Finds the parameters declared in GLSL source, in order
+pub fn parse(source: &str) -> Result<Vec<Param>, String> {
+    let mut params: Vec<Param> = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let Some(declaration) = line
+            .trim_start()
+            .strip_prefix("//")
+            .and_then(|comment| comment.trim_start().strip_prefix(DECLARATION_PREFIX))
+        else {
+            continue;
+        };
+
+        let param = parse_declaration(declaration).map_err(|e| format!("line {}: {}", i + 1, e))?;
+
+        if params.iter().any(|other| other.name == param.name) {
+            return Err(format!("line {}: duplicate parameter \"{}\"", i + 1, param.name));
+        }
+
+        params.push(param);
+    }
+
+    Ok(params)
+}
+
+/// Adds the parameters of another pass. Passes can share parameters as long as they are declared
+/// the same way.
+pub fn merge(params: &mut Vec<Param>, other: Vec<Param>) -> Result<(), String> {
+    for param in other {
+        match params.iter().find(|existing| existing.name == param.name) {
+            Some(existing) if *existing != param => {
+                return Err(format!("parameter \"{}\" is declared differently", param.name));
+            }
+            Some(_) => (),
+            None => params.push(param),
+        }
+    }
+
+    Ok(())
+}
+
+/// Uniform block that makes the parameters available to shaders by name. Empty without parameters.
+pub fn glsl_block(params: &[Param]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let members: Vec<String> = params
+        .iter()
+        .map(|param| format!("    {} {};\n", param.ty.glsl_name(), param.name))
+        .collect();
+
+    format!(
+        "layout(set = 0, binding = {}, std140) uniform VxtrParams {{\n{}}};\n",
+        PARAMS_BINDING,
+        members.concat()
+    )
+}
+
+/// Size in bytes of the uniform block from `glsl_block`
+pub fn block_size(params: &[Param]) -> usize {
+    block_layout(params).1
+}
+
+/// Offsets of the members under the std140 layout, and the size of the whole block
+fn block_layout(params: &[Param]) -> (Vec<usize>, usize) {
+    let mut offsets = Vec::with_capacity(params.len());
+    let mut size: usize = 0;
+
+    for param in params {
+        let offset = size.next_multiple_of(param.ty.alignment());
+
+        offsets.push(offset);
+        size = offset + param.ty.components() * 4;
+    }
+
+    // Blocks are rounded up to the alignment of a vec4
+    (offsets, size.next_multiple_of(16))
+}
+
+/// `TYPE NAME = DEFAULT [MIN, MAX]`, where the default is one number for every component or one
+/// per component, optionally in parentheses or a constructor like `vec3(0, 2, 0)`
+fn parse_declaration(declaration: &str) -> Result<Param, String> {
+    let invalid = || format!("expected \"{} TYPE NAME = DEFAULT [MIN, MAX]\"", DECLARATION_PREFIX);
+
+    let (ty, rest) = declaration.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+    let (name, rest) = rest.split_once('=').ok_or_else(invalid)?;
+    let (default, range) = rest.split_once('[').ok_or_else(invalid)?;
+    let range = range.trim_end().strip_suffix(']').ok_or_else(invalid)?;
+
+    let ty = ParamType::from_name(ty).ok_or_else(|| format!("unsupported type \"{}\"", ty))?;
+    let name = name.trim();
+
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !is_identifier {
+        return Err(format!("invalid parameter name \"{}\"", name));
+    }
+
+    let bounds = parse_numbers(range)?;
+
+    let &[min, max] = bounds.as_slice() else {
+        return Err(invalid());
+    };
+
+    if min >= max {
+        return Err(format!("empty range of \"{}\"", name));
+    }
+
+    let default = default.trim();
+    let default = default.strip_prefix(ty.glsl_name()).unwrap_or(default).trim();
+    let default = default.strip_prefix('(').and_then(|d| d.strip_suffix(')')).unwrap_or(default);
+
+    let components = parse_numbers(default)?;
+    let mut values = [0.0; 4];
+
+    match components.len() {
+        1 => values[..ty.components()].fill(components[0]),
+        n if n == ty.components() => values[..n].copy_from_slice(&components),
+        n => return Err(format!("{} has {} components, got {}", name, ty.components(), n)),
+    }
+
+    if values[..ty.components()].iter().any(|value| !(min..=max).contains(value)) {
+        return Err(format!("default of \"{}\" is outside of its range", name));
+    }
+
+    Ok(Param {
+        name: name.to_string(),
+        ty,
+        default: values,
+        min,
+        max,
+    })
+}
+
+fn parse_numbers(list: &str) -> Result<Vec<f32>, String> {
+    list.split(',')
+        .map(|number| {
+            let number = number.trim();
+            number.parse().map_err(|_| format!("invalid number \"{}\"", number))
+        })
+        .collect()
+}
+
+impl ParamValues {
+    pub fn new(params: Vec<Param>) -> Self {
+        let values = params.iter().map(|param| param.default).collect();
+
+        Self {
+            params,
+            values,
+            selected: 0,
+            component: 0,
+        }
+    }
+
+    /// Switches to the parameters of a reloaded project, keeping the values of those that didn't
+    /// change type
+    pub fn update(&mut self, params: Vec<Param>) {
+        let mut updated = Self::new(params);
+
+        for (param, value) in updated.params.iter().zip(&mut updated.values) {
+            let old =
+                self.params.iter().position(|old| old.name == param.name && old.ty == param.ty);
+
+            if let Some(old) = old {
+                *value = self.values[old].map(|component| component.clamp(param.min, param.max));
+            }
+        }
+
+        *self = updated;
+    }
+
+    /// Values laid out like the uniform block from `glsl_block`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (offsets, size) = block_layout(&self.params);
+        let mut bytes = vec![0; size];
+
+        for ((param, value), offset) in self.params.iter().zip(&self.values).zip(offsets) {
+            for (i, &component) in value[..param.ty.components()].iter().enumerate() {
+                #[allow(clippy::cast_possible_truncation)]
+                let component_bytes = match param.ty {
+                    ParamType::Int => (component.round() as i32).to_ne_bytes(),
+                    _ => component.to_ne_bytes(),
+                };
+
+                let start = offset + i * 4;
+                bytes[start..start + 4].copy_from_slice(&component_bytes);
+            }
+        }
+
+        bytes
+    }
+
+    /// Tab and Shift+Tab select a component, ] and [ increase and decrease it (ten times as much
+    /// with Shift), \ resets it and P prints every value. Returns whether the key was used.
+    pub fn handle_key(&mut self, key: Key, mods: Modifiers) -> bool {
+        if self.params.is_empty() {
+            return false;
+        }
+
+        let step = if mods.shift() {
+            STEP_FRACTION * 10.0
+        } else {
+            STEP_FRACTION
+        };
+
+        match key {
+            Key::Tab => {
+                self.select_next(!mods.shift());
+                eprintln!("Selected {}", self.describe_selected());
+            }
+            Key::RightBracket => self.change_selected(step),
+            Key::LeftBracket => self.change_selected(-step),
+            Key::Backslash => {
+                let default = self.params[self.selected].default[self.component];
+
+                self.values[self.selected][self.component] = default;
+                eprintln!("{}", self.describe_selected());
+            }
+            Key::P => self.print(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn print(&self) {
+        if self.params.is_empty() {
+            eprintln!("No parameters");
+        }
+
+        for (param, value) in self.params.iter().zip(&self.values) {
+            let values: Vec<String> =
+                value[..param.ty.components()].iter().map(|v| format_value(param, *v)).collect();
+
+            eprintln!(
+                "{} {} = {} [{}, {}]",
+                param.ty.glsl_name(),
+                param.name,
+                values.join(", "),
+                param.min,
+                param.max
+            );
+        }
+    }
+
+    /// Moves through the components of every parameter, wrapping around
+    fn select_next(&mut self, forward: bool) {
+        let slots: Vec<(usize, usize)> = self
+            .params
+            .iter()
+            .enumerate()
+            .flat_map(|(i, param)| (0..param.ty.components()).map(move |c| (i, c)))
+            .collect();
+
+        let current = slots.iter().position(|&slot| slot == (self.selected, self.component));
+        let current = current.unwrap_or(0);
+        let next = if forward {
+            current + 1
+        } else {
+            current + slots.len() - 1
+        };
+
+        (self.selected, self.component) = slots[next % slots.len()];
+    }
+
+    /// `amount` is a fraction of the parameter's range
+    fn change_selected(&mut self, amount: f32) {
+        let param = &self.params[self.selected];
+        let mut delta = amount * (param.max - param.min);
+
+        // Integers always move by at least one
+        if param.ty == ParamType::Int {
+            delta = delta.signum() * delta.abs().round().max(1.0);
+        }
+
+        let value = &mut self.values[self.selected][self.component];
+        *value = (*value + delta).clamp(param.min, param.max);
+
+        eprintln!("{}", self.describe_selected());
+    }
+
+    fn describe_selected(&self) -> String {
+        let param = &self.params[self.selected];
+        let value = format_value(param, self.values[self.selected][self.component]);
+
+        if param.ty.components() == 1 {
+            format!("{} = {}", param.name, value)
+        } else {
+            format!("{}.{} = {}", param.name, COMPONENT_NAMES[self.component], value)
+        }
+    }
+}
+
+fn format_value(param: &Param, value: f32) -> String {
+    match param.ty {
+        ParamType::Int => format!("{}", value.round()),
+        _ => format!("{:.3}", value),
+    }
+}
+
+impl Presets {
+    /// Uses the default preset if `name` is `None`
+    pub fn new(path: PathBuf, name: Option<String>) -> Self {
+        Self {
+            path,
+            named: name.is_some(),
+            name: name.unwrap_or_else(|| DEFAULT_PRESET.to_string()),
+        }
+    }
+
+    /// Loads the preset if one was named
+    pub fn apply_named(&self, values: &mut ParamValues) -> Result<(), String> {
+        if self.named {
+            self.load(values)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the parameters stored in the preset, leaving the rest as they are
+    pub fn load(&self, values: &mut ParamValues) -> Result<(), String> {
+        let file = self.read()?;
+        let preset = file
+            .get(&self.name)
+            .ok_or_else(|| format!("no preset \"{}\" in {}", self.name, self.path.display()))?;
+
+        for (param, value) in values.params.iter().zip(&mut values.values) {
+            let stored = match preset.get(&param.name) {
+                Some(PresetValue::Scalar(scalar)) => vec![*scalar],
+                Some(PresetValue::Vector(vector)) => vector.clone(),
+                None => continue,
+            };
+
+            for (component, stored) in value[..param.ty.components()].iter_mut().zip(stored) {
+                *component = stored.clamp(param.min, param.max);
+            }
+        }
+
+        eprintln!("Loaded preset \"{}\" from {}", self.name, self.path.display());
+
+        Ok(())
+    }
+
+    /// Replaces the preset with the current values, keeping the other presets in the file
+    pub fn save(&self, values: &ParamValues) -> Result<(), String> {
+        let mut file = self.read()?;
+
+        let preset = values
+            .params
+            .iter()
+            .zip(&values.values)
+            .map(|(param, value)| {
+                let value = match param.ty.components() {
+                    1 => PresetValue::Scalar(value[0]),
+                    n => PresetValue::Vector(value[..n].to_vec()),
+                };
+
+                (param.name.clone(), value)
+            })
+            .collect();
+
+        file.insert(self.name.clone(), preset);
+
+        let contents = toml::to_string_pretty(&file)
+            .map_err(|e| format!("failed to serialize {}: {}", self.path.display(), e))?;
+
+        fs::write(&self.path, contents)
+            .map_err(|e| format!("failed to write {}: {}", self.path.display(), e))?;
+
+        eprintln!("Saved preset \"{}\" to {}", self.name, self.path.display());
+
+        Ok(())
+    }
+
+    /// Empty if the file doesn't exist yet
+    fn read(&self) -> Result<PresetFile, String> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(PresetFile::new()),
+            Err(e) => return Err(format!("failed to read {}: {}", self.path.display(), e)),
+        };
+
+        toml::from_str(&contents)
+            .map_err(|e| format!("failed to parse {}: {}", self.path.display(), e))
+    }
+}
@@ -0,0 +1,667 @@
+use glfw_sys::*;
+
+use vk::DebugName;
+
+use crate::params::{self, PARAMS_BINDING};
+use crate::project::{CompiledPass, CompiledProject, Filter, Input, Texture, Wrap, CHANNEL_COUNT};
+
+const VERT_COMPILED: &[u8] = include_bytes!("../build/shader.vert.spv");
+
+/// Float format so that simulations can store values outside of [0, 1]
+const BUFFER_FORMAT: VkFormat = VK_FORMAT_R16G16B16A16_SFLOAT;
+
+/// Texture values are passed to shaders as they are stored, like on Shadertoy
+const TEXTURE_FORMAT: VkFormat = VK_FORMAT_R8G8B8A8_UNORM;
+
+/// Bound to channels without an input, so that every descriptor is valid
+const DUMMY_FORMAT: VkFormat = VK_FORMAT_R8G8B8A8_UNORM;
+
+/// Stages that can sample channels and read push constants
+const SHADER_STAGES: u32 = VK_SHADER_STAGE_FRAGMENT_BIT | VK_SHADER_STAGE_COMPUTE_BIT;
+
+/// Pipeline stages of `SHADER_STAGES`, for barriers around buffer reads
+const SHADER_PIPELINE_STAGES: u32 =
+    VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT | VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT;
+
+/// Storage image that a compute pass writes its buffer through, bound after the channels
+const OUTPUT_BINDING: u32 = 4;
+
+/// Compute shaders must declare `layout(local_size_x = 8, local_size_y = 8) in;`
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Layouts shared by every pass. They don't depend on the shaders, so they outlive reloads.
+pub struct PassLayout {
+    pub pipeline_layout: vk::PipelineLayout,
+    set_layout: vk::DescriptorSetLayout,
+}
+
+/// Pipelines and images for every pass of a project
+pub struct MultiPass {
+    passes: Vec<Pass>,
+    buffers: Vec<PassBuffer>,
+    #[allow(unused)] // Must outlive the descriptor sets
+    textures: Vec<PassTexture>,
+    #[allow(unused)] // Must outlive the descriptor sets
+    samplers: Vec<(Filter, Wrap, vk::Sampler)>,
+    buffer_render_pass: vk::RenderPass,
+    extent: VkExtent2D,
+    #[allow(unused)] // Must outlive the descriptor sets
+    dummy_view: vk::ImageView,
+    #[allow(unused)] // Must outlive the view
+    dummy_image: vk::Image,
+    /// Parameter values, updated at the start of every frame
+    params_buffer: vk::Buffer,
+    #[allow(unused)] // Must outlive the descriptor sets
+    descriptor_pool: vk::DescriptorPool,
+}
+
+struct Pass {
+    /// Shown in debug labels and object names
+    name: String,
+    pipeline: PassPipeline,
+    /// Indexed by frame parity
+    descriptor_sets: [vk::DescriptorSet; 2],
+}
+
+enum PassPipeline {
+    Graphics(vk::Pipeline),
+    /// Only buffer passes can be compute passes
+    Compute(vk::ComputePipeline),
+}
+
+/// Ping-pong images of a buffer pass: on frames with parity `p` the pass renders into image `p`
+/// while image `1 - p` still holds the previous frame
+struct PassBuffer {
+    framebuffers: [vk::Framebuffer; 2],
+    #[allow(unused)] // Must outlive the framebuffers and descriptor sets
+    views: [vk::ImageView; 2],
+    images: [vk::Image; 2],
+}
+
+struct PassTexture {
+    view: vk::ImageView,
+    #[allow(unused)] // Must outlive the view
+    image: vk::Image,
+}
+
+impl PassLayout {
+    pub fn new<PushConstT>(device: &vk::Device) -> Result<Self, String> {
+        let mut bindings: Vec<vk::DescriptorBinding> = (0..CHANNEL_COUNT)
+            .map(|channel| vk::DescriptorBinding {
+                binding: channel.try_into().unwrap(),
+                descriptor_type: VK_DESCRIPTOR_TYPE_COMBINED_IMAGE_SAMPLER,
+                count: 1,
+                stages: SHADER_STAGES,
+            })
+            .collect();
+
+        bindings.push(vk::DescriptorBinding {
+            binding: OUTPUT_BINDING,
+            descriptor_type: VK_DESCRIPTOR_TYPE_STORAGE_IMAGE,
+            count: 1,
+            stages: VK_SHADER_STAGE_COMPUTE_BIT,
+        });
+
+        bindings.push(vk::DescriptorBinding {
+            binding: PARAMS_BINDING,
+            descriptor_type: VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER,
+            count: 1,
+            stages: SHADER_STAGES,
+        });
+
+        let set_layout = device.create_descriptor_set_layout(&bindings)?;
+        let pipeline_layout =
+            device.create_pipeline_layout::<PushConstT>(SHADER_STAGES, &[&set_layout])?;
+
+        set_layout.set_name("set_layout");
+        pipeline_layout.set_name("pipeline_layout");
+
+        Ok(Self {
+            pipeline_layout,
+            set_layout,
+        })
+    }
+}
+
+impl MultiPass {
+    /// Creates buffer images of the given size, cleared to zero, and uploads textures.
+    /// `render_pass` is the one the image pass renders with.
+    pub fn new(
+        device: &vk::Device,
+        command_pool: &vk::CommandPool,
+        queue: &vk::Queue,
+        layout: &PassLayout,
+        render_pass: &vk::RenderPass,
+        extent: VkExtent2D,
+        compiled: &CompiledProject,
+    ) -> Result<Self, String> {
+        let buffer_render_pass =
+            device.create_render_pass(BUFFER_FORMAT, VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL)?;
+
+        let buffer_count = compiled.passes.len() - 1;
+        let buffers: Vec<PassBuffer> = (0..buffer_count)
+            .map(|_| PassBuffer::new(device, &buffer_render_pass, extent))
+            .collect::<Result<_, _>>()?;
+
+        let dummy_image = device.create_image(
+            VkExtent2D {
+                width: 1,
+                height: 1,
+            },
+            DUMMY_FORMAT,
+            VK_IMAGE_USAGE_SAMPLED_BIT | VK_IMAGE_USAGE_TRANSFER_DST_BIT,
+        )?;
+        let dummy_view = device.create_image_view(&dummy_image)?;
+
+        clear_images(command_pool, queue, &buffers, &dummy_image)?;
+
+        let textures: Vec<PassTexture> = compiled
+            .textures
+            .iter()
+            .map(|texture| PassTexture::new(device, command_pool, queue, texture))
+            .collect::<Result<_, _>>()?;
+
+        let samplers = create_samplers(device, compiled)?;
+
+        let descriptor_pool = create_descriptor_pool(device, compiled)?;
+
+        let params_buffer = create_params_buffer(device, compiled)?;
+
+        let passes = compiled
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(i, pass)| -> Result<Pass, String> {
+                let target_render_pass = if i == buffer_count {
+                    render_pass
+                } else {
+                    &buffer_render_pass
+                };
+
+                let descriptor_sets = [0, 1].map(|parity| {
+                    let mut set = descriptor_pool.allocate_set(&layout.set_layout)?;
+
+                    for (binding, channel) in pass.channels.iter().enumerate() {
+                        let view = match channel.input {
+                            Input::Unused => &dummy_view,
+                            // Earlier buffers were already rendered this frame
+                            Input::Buffer(buffer) if buffer < i => &buffers[buffer].views[parity],
+                            Input::Buffer(buffer) => &buffers[buffer].views[1 - parity],
+                            Input::Texture(texture) => &textures[texture].view,
+                        };
+
+                        let (_, _, sampler) = samplers
+                            .iter()
+                            .find(|&&(f, w, _)| (f, w) == (channel.filter, channel.wrap))
+                            .unwrap();
+
+                        set.write_combined_image_sampler(
+                            binding.try_into().unwrap(),
+                            view,
+                            sampler,
+                            VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL,
+                        );
+                    }
+
+                    set.write_buffer(
+                        PARAMS_BINDING,
+                        VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER,
+                        &params_buffer,
+                        0,
+                        VK_WHOLE_SIZE as u64,
+                    );
+
+                    if pass.is_compute {
+                        set.write_image(
+                            OUTPUT_BINDING,
+                            VK_DESCRIPTOR_TYPE_STORAGE_IMAGE,
+                            &buffers[i].views[parity],
+                            None,
+                            VK_IMAGE_LAYOUT_GENERAL,
+                        );
+                    }
+
+                    Ok::<_, vk::Error>(set)
+                });
+
+                Ok(Pass {
+                    name: pass.name.clone(),
+                    pipeline: PassPipeline::new(device, target_render_pass, layout, pass)?,
+                    descriptor_sets: both(descriptor_sets)?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let multi_pass = Self {
+            passes,
+            buffers,
+            textures,
+            samplers,
+            buffer_render_pass,
+            extent,
+            dummy_view,
+            dummy_image,
+            params_buffer,
+            descriptor_pool,
+        };
+
+        multi_pass.set_names();
+
+        Ok(multi_pass)
+    }
+
+    /// Names the objects after their fields, prefixed with the name of the pass they belong to
+    fn set_names(&self) {
+        self.buffer_render_pass.set_name("buffer_render_pass");
+        self.dummy_view.set_name("dummy_view");
+        self.dummy_image.set_name("dummy_image");
+        self.params_buffer.set_name("params_buffer");
+        self.descriptor_pool.set_name("descriptor_pool");
+
+        for pass in &self.passes {
+            match &pass.pipeline {
+                PassPipeline::Graphics(pipeline) => pipeline.set_name(&pass.name),
+                PassPipeline::Compute(pipeline) => pipeline.set_name(&pass.name),
+            }
+
+            for (parity, set) in pass.descriptor_sets.iter().enumerate() {
+                set.set_name(&format!("{} descriptor_sets[{}]", pass.name, parity));
+            }
+        }
+
+        // Only buffer passes have a buffer, and they come first
+        for (pass, buffer) in self.passes.iter().zip(&self.buffers) {
+            for parity in 0..2 {
+                buffer.images[parity].set_name(&format!("{} images[{}]", pass.name, parity));
+                buffer.views[parity].set_name(&format!("{} views[{}]", pass.name, parity));
+                buffer.framebuffers[parity]
+                    .set_name(&format!("{} framebuffers[{}]", pass.name, parity));
+            }
+        }
+    }
+
+    /// Records the buffer passes followed by the image pass into `framebuffer`. `draw` is called
+    /// inside each render pass once its pipeline, descriptor set and push constants are set.
+    /// `pass_done` is called with the index of each pass after it, outside of render passes.
+    /// `params` are the parameter values, laid out like `params::glsl_block` declares them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record<PushConstT, F, G>(
+        &self,
+        handle: &vk::CommandBufferRecording,
+        layout: &PassLayout,
+        frame: i32,
+        render_pass: &vk::RenderPass,
+        framebuffer: &vk::Framebuffer,
+        push_constants: &PushConstT,
+        params: &[u8],
+        draw: F,
+        pass_done: G,
+    ) where
+        F: Fn(&vk::CommandBufferRecording),
+        G: Fn(&vk::CommandBufferRecording, usize),
+    {
+        let parity = usize::from(frame % 2 != 0);
+
+        self.record_params_update(handle, params);
+
+        for (i, (pass, buffer)) in self.passes.iter().zip(&self.buffers).enumerate() {
+            let descriptor_set = &pass.descriptor_sets[parity];
+
+            handle.begin_label(&pass.name);
+
+            match &pass.pipeline {
+                PassPipeline::Graphics(pipeline) => {
+                    // The image was last sampled by the previous frame
+                    handle.pipeline_barrier(
+                        SHADER_PIPELINE_STAGES,
+                        VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
+                        0,
+                        0,
+                    );
+
+                    self.record_graphics_pass(
+                        handle,
+                        layout,
+                        pipeline,
+                        descriptor_set,
+                        (&self.buffer_render_pass, &buffer.framebuffers[parity]),
+                        push_constants,
+                        &draw,
+                    );
+
+                    handle.pipeline_barrier(
+                        VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
+                        SHADER_PIPELINE_STAGES,
+                        VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT,
+                        VK_ACCESS_SHADER_READ_BIT,
+                    );
+                }
+                PassPipeline::Compute(pipeline) => self.record_compute_pass(
+                    handle,
+                    layout,
+                    pipeline,
+                    descriptor_set,
+                    &buffer.images[parity],
+                    push_constants,
+                ),
+            }
+
+            handle.end_label();
+
+            pass_done(handle, i);
+        }
+
+        let image_pass = self.passes.last().unwrap();
+
+        handle.begin_label(&image_pass.name);
+
+        let PassPipeline::Graphics(pipeline) = &image_pass.pipeline else {
+            unreachable!("the image pass is never a compute pass");
+        };
+
+        self.record_graphics_pass(
+            handle,
+            layout,
+            pipeline,
+            &image_pass.descriptor_sets[parity],
+            (render_pass, framebuffer),
+            push_constants,
+            &draw,
+        );
+
+        handle.end_label();
+
+        pass_done(handle, self.passes.len() - 1);
+    }
+
+    fn record_params_update(&self, handle: &vk::CommandBufferRecording, params: &[u8]) {
+        if params.is_empty() {
+            return;
+        }
+
+        handle.begin_label("Update parameters");
+
+        // The previous frame may still be reading the old values
+        handle.pipeline_barrier(SHADER_PIPELINE_STAGES, VK_PIPELINE_STAGE_TRANSFER_BIT, 0, 0);
+
+        handle.update_buffer(&self.params_buffer, 0, params);
+
+        handle.pipeline_barrier(
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+            SHADER_PIPELINE_STAGES,
+            VK_ACCESS_TRANSFER_WRITE_BIT,
+            VK_ACCESS_UNIFORM_READ_BIT,
+        );
+
+        handle.end_label();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record_graphics_pass<PushConstT, F>(
+        &self,
+        handle: &vk::CommandBufferRecording,
+        layout: &PassLayout,
+        pipeline: &vk::Pipeline,
+        descriptor_set: &vk::DescriptorSet,
+        (render_pass, framebuffer): (&vk::RenderPass, &vk::Framebuffer),
+        push_constants: &PushConstT,
+        draw: &F,
+    ) where
+        F: Fn(&vk::CommandBufferRecording),
+    {
+        let clear_color = [0.0, 0.0, 0.0, 1.0];
+
+        handle.begin_render_pass(clear_color, render_pass, framebuffer, self.extent);
+
+        handle.bind_pipeline(VK_PIPELINE_BIND_POINT_GRAPHICS, pipeline);
+        handle.set_viewport_and_scissor(self.extent);
+
+        handle.bind_descriptor_sets(
+            VK_PIPELINE_BIND_POINT_GRAPHICS,
+            &layout.pipeline_layout,
+            0,
+            &[descriptor_set],
+            &[],
+        );
+
+        handle.push_constants(&layout.pipeline_layout, SHADER_STAGES, 0, push_constants);
+
+        draw(handle);
+
+        handle.end_render_pass();
+    }
+
+    fn record_compute_pass<PushConstT>(
+        &self,
+        handle: &vk::CommandBufferRecording,
+        layout: &PassLayout,
+        pipeline: &vk::ComputePipeline,
+        descriptor_set: &vk::DescriptorSet,
+        image: &vk::Image,
+        push_constants: &PushConstT,
+    ) {
+        // Same as for graphics buffer passes, with the layout change that the render pass does
+        handle.transition_image_layout(
+            image,
+            VK_IMAGE_LAYOUT_UNDEFINED,
+            VK_IMAGE_LAYOUT_GENERAL,
+            SHADER_PIPELINE_STAGES,
+            VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+            0,
+            VK_ACCESS_SHADER_WRITE_BIT,
+        );
+
+        handle.bind_compute_pipeline(pipeline);
+
+        handle.bind_descriptor_sets(
+            VK_PIPELINE_BIND_POINT_COMPUTE,
+            &layout.pipeline_layout,
+            0,
+            &[descriptor_set],
+            &[],
+        );
+
+        handle.push_constants(&layout.pipeline_layout, SHADER_STAGES, 0, push_constants);
+
+        handle.dispatch(
+            self.extent.width.div_ceil(WORKGROUP_SIZE),
+            self.extent.height.div_ceil(WORKGROUP_SIZE),
+            1,
+        );
+
+        handle.transition_image_layout(
+            image,
+            VK_IMAGE_LAYOUT_GENERAL,
+            VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL,
+            VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+            SHADER_PIPELINE_STAGES,
+            VK_ACCESS_SHADER_WRITE_BIT,
+            VK_ACCESS_SHADER_READ_BIT,
+        );
+    }
+}
+
+impl PassPipeline {
+    fn new(
+        device: &vk::Device,
+        render_pass: &vk::RenderPass,
+        layout: &PassLayout,
+        pass: &CompiledPass,
+    ) -> Result<Self, String> {
+        if pass.is_compute {
+            let shader = device.create_shader(&pass.spirv, vk::ShaderType::Compute)?;
+
+            Ok(Self::Compute(device.create_compute_pipeline(&shader, &layout.pipeline_layout)?))
+        } else {
+            Ok(Self::Graphics(create_pipeline(
+                device,
+                render_pass,
+                &layout.pipeline_layout,
+                &pass.spirv,
+            )?))
+        }
+    }
+}
+
+impl PassBuffer {
+    fn new(
+        device: &vk::Device,
+        render_pass: &vk::RenderPass,
+        extent: VkExtent2D,
+    ) -> Result<Self, String> {
+        let usage = VK_IMAGE_USAGE_COLOR_ATTACHMENT_BIT
+            | VK_IMAGE_USAGE_SAMPLED_BIT
+            | VK_IMAGE_USAGE_STORAGE_BIT
+            | VK_IMAGE_USAGE_TRANSFER_DST_BIT;
+
+        let images = both([0, 1].map(|_| device.create_image(extent, BUFFER_FORMAT, usage)))?;
+        let views = both(images.each_ref().map(|image| device.create_image_view(image)))?;
+        let framebuffers = both(
+            views.each_ref().map(|view| device.create_framebuffer(render_pass, view, extent)),
+        )?;
+
+        Ok(Self {
+            framebuffers,
+            views,
+            images,
+        })
+    }
+}
+
+impl PassTexture {
+    fn new(
+        device: &vk::Device,
+        command_pool: &vk::CommandPool,
+        queue: &vk::Queue,
+        texture: &Texture,
+    ) -> Result<Self, String> {
+        let image = device.create_image_with_data(
+            command_pool,
+            queue,
+            VkExtent2D {
+                width: texture.width,
+                height: texture.height,
+            },
+            TEXTURE_FORMAT,
+            VK_IMAGE_USAGE_SAMPLED_BIT,
+            VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL,
+            &texture.pixels,
+            texture.mipmaps,
+        )?;
+        let view = device.create_image_view(&image)?;
+
+        Ok(Self { view, image })
+    }
+}
+
+/// Unwraps a pair of results, like the ones created for each parity of the frame
+fn both<T, E>([first, second]: [Result<T, E>; 2]) -> Result<[T; 2], E> {
+    Ok([first?, second?])
+}
+
+/// Two sets per pass, one for each parity of the frame
+fn create_descriptor_pool(
+    device: &vk::Device,
+    compiled: &CompiledProject,
+) -> Result<vk::DescriptorPool, String> {
+    let set_count: u32 = (compiled.passes.len() * 2).try_into().unwrap();
+
+    let pool = device.create_descriptor_pool(
+        set_count,
+        &[
+            (
+                VK_DESCRIPTOR_TYPE_COMBINED_IMAGE_SAMPLER,
+                set_count * u32::try_from(CHANNEL_COUNT).unwrap(),
+            ),
+            (VK_DESCRIPTOR_TYPE_STORAGE_IMAGE, set_count),
+            (VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER, set_count),
+        ],
+    )?;
+
+    Ok(pool)
+}
+
+/// One sampler for each filter and wrap combination the channels use
+fn create_samplers(
+    device: &vk::Device,
+    compiled: &CompiledProject,
+) -> Result<Vec<(Filter, Wrap, vk::Sampler)>, String> {
+    let mut samplers: Vec<(Filter, Wrap, vk::Sampler)> = Vec::new();
+
+    for channel in compiled.passes.iter().flat_map(|pass| &pass.channels) {
+        let (filter, wrap) = (channel.filter, channel.wrap);
+
+        if !samplers.iter().any(|&(f, w, _)| (f, w) == (filter, wrap)) {
+            samplers.push((filter, wrap, create_sampler(device, filter, wrap)?));
+        }
+    }
+
+    Ok(samplers)
+}
+
+fn create_sampler(device: &vk::Device, filter: Filter, wrap: Wrap) -> Result<vk::Sampler, String> {
+    let (filter, mipmap_mode) = match filter {
+        Filter::Nearest => (VK_FILTER_NEAREST, VK_SAMPLER_MIPMAP_MODE_NEAREST),
+        Filter::Linear => (VK_FILTER_LINEAR, VK_SAMPLER_MIPMAP_MODE_NEAREST),
+        Filter::Mipmap => (VK_FILTER_LINEAR, VK_SAMPLER_MIPMAP_MODE_LINEAR),
+    };
+
+    let address_mode = match wrap {
+        Wrap::Clamp => VK_SAMPLER_ADDRESS_MODE_CLAMP_TO_EDGE,
+        Wrap::Repeat => VK_SAMPLER_ADDRESS_MODE_REPEAT,
+    };
+
+    Ok(device.create_sampler(filter, mipmap_mode, address_mode)?)
+}
+
+/// Puts every image in the layout it is sampled in, so that the first frame reads zeros
+fn clear_images(
+    command_pool: &vk::CommandPool,
+    queue: &vk::Queue,
+    buffers: &[PassBuffer],
+    dummy_image: &vk::Image,
+) -> Result<(), String> {
+    let mut cmd_buffer = command_pool.create_command_buffer()?;
+
+    cmd_buffer.record_with_flags(VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT, |handle| {
+        let images = buffers.iter().flat_map(|buffer| &buffer.images);
+
+        for image in images.chain([dummy_image]) {
+            handle.clear_image(image, [0.0; 4], VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL);
+        }
+    })?;
+
+    queue.submit(&cmd_buffer)?;
+    queue.wait_idle()?;
+
+    Ok(())
+}
+
+fn create_pipeline(
+    device: &vk::Device,
+    render_pass: &vk::RenderPass,
+    pipeline_layout: &vk::PipelineLayout,
+    frag_compiled: &[u8],
+) -> Result<vk::Pipeline, String> {
+    let vert_shader = device.create_shader(VERT_COMPILED, vk::ShaderType::Vertex)?;
+    let frag_shader = device.create_shader(frag_compiled, vk::ShaderType::Fragment)?;
+
+    Ok(device.create_pipeline(&[vert_shader, frag_shader], render_pass, pipeline_layout)?)
+}
+
+/// Written with commands rather than mapped, so it can be device local. Never empty, so that the
+/// descriptor is valid even without parameters.
+fn create_params_buffer(
+    device: &vk::Device,
+    compiled: &CompiledProject,
+) -> Result<vk::Buffer, String> {
+    let size = params::block_size(&compiled.params).max(16);
+
+    let buffer = device.create_buffer(
+        u64::try_from(size).unwrap(),
+        VK_BUFFER_USAGE_UNIFORM_BUFFER_BIT | VK_BUFFER_USAGE_TRANSFER_DST_BIT,
+        VK_MEMORY_PROPERTY_DEVICE_LOCAL_BIT,
+    )?;
+
+    Ok(buffer)
+}
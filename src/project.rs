@@ -0,0 +1,427 @@
+use serde::Deserialize;
+
+use crate::params::{self, Param};
+use crate::shaders;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File that describes a multipass project, placed in the project's directory
+pub const MANIFEST_NAME: &str = "project.toml";
+
+/// Parameter presets of a multipass project, placed next to the manifest
+const PRESETS_NAME: &str = "presets.toml";
+
+/// Number of inputs a pass can sample, bound as `iChannel0` to `iChannel3`
+pub const CHANNEL_COUNT: usize = 4;
+
+const IMAGE_PASS_NAME: &str = "Image";
+
+/// Buffer passes rendered in order into their own images, followed by the image pass that ends
+/// up on screen. A lone shader is a project with just the image pass.
+pub struct Project {
+    passes: Vec<PassSource>,
+    manifest: Option<PathBuf>,
+}
+
+struct PassSource {
+    name: String,
+    shader: PathBuf,
+    channels: [ChannelSource; CHANNEL_COUNT],
+}
+
+#[derive(Clone, Default)]
+enum ChannelSource {
+    #[default]
+    Unused,
+    Buffer {
+        index: usize,
+        filter: Filter,
+        wrap: Wrap,
+    },
+    Texture {
+        path: PathBuf,
+        filter: Filter,
+        wrap: Wrap,
+        vflip: Option<bool>,
+    },
+}
+
+/// Everything needed to create the passes on the GPU
+pub struct CompiledProject {
+    /// The image pass is last
+    pub passes: Vec<CompiledPass>,
+    pub textures: Vec<Texture>,
+    /// Declared by any of the passes, every pass can use all of them
+    pub params: Vec<Param>,
+}
+
+pub struct CompiledPass {
+    /// Buffer name from the manifest, or "Image"
+    pub name: String,
+    pub spirv: Vec<u8>,
+    /// Compute passes write their buffer as a storage image instead of rendering to it
+    pub is_compute: bool,
+    pub channels: [Channel; CHANNEL_COUNT],
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Channel {
+    pub input: Input,
+    pub filter: Filter,
+    pub wrap: Wrap,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Unused,
+    /// Output of the buffer pass with this index. Buffers that come earlier than the sampling pass
+    /// give this frame's output, the rest (including the pass itself) give the previous frame's.
+    Buffer(usize),
+    /// Index into `CompiledProject::textures`
+    Texture(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    Nearest,
+    Linear,
+    /// Linear with trilinear filtering between generated mip levels. Textures only.
+    Mipmap,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Wrap {
+    Clamp,
+    Repeat,
+}
+
+/// Decoded RGBA8 pixels, tightly packed
+pub struct Texture {
+    pub pixels: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub mipmaps: bool,
+    path: PathBuf,
+    vflip: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    #[serde(default)]
+    buffer: Vec<BufferManifest>,
+    image: ImageManifest,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BufferManifest {
+    name: String,
+    shader: PathBuf,
+    #[serde(default)]
+    channels: Vec<ChannelManifest>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImageManifest {
+    shader: PathBuf,
+    #[serde(default)]
+    channels: Vec<ChannelManifest>,
+}
+
+/// Either a buffer name, empty for an unused channel, or a table with the input and its options
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChannelManifest {
+    Name(String),
+    Options(ChannelOptions),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChannelOptions {
+    buffer: Option<String>,
+    texture: Option<PathBuf>,
+    filter: Option<Filter>,
+    wrap: Option<Wrap>,
+    vflip: Option<bool>,
+}
+
+impl Project {
+    /// Loads a project from a directory containing `project.toml`, from the manifest itself, or
+    /// from a single fragment shader
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let manifest = if path.is_dir() {
+            path.join(MANIFEST_NAME)
+        } else if path.extension().is_some_and(|ext| ext == "toml") {
+            path.to_path_buf()
+        } else {
+            return Ok(Self {
+                passes: vec![PassSource {
+                    name: IMAGE_PASS_NAME.to_string(),
+                    shader: path.to_path_buf(),
+                    channels: Default::default(),
+                }],
+                manifest: None,
+            });
+        };
+
+        let passes = parse_manifest(&manifest)?;
+
+        Ok(Self {
+            passes,
+            manifest: Some(manifest),
+        })
+    }
+
+    /// Compiles every pass and decodes the textures they sample
+    pub fn compile(&self) -> Result<CompiledProject, String> {
+        let image_pass = self.passes.len() - 1;
+        let mut passes = Vec::with_capacity(self.passes.len());
+        let mut textures = Vec::new();
+        let mut params = Vec::new();
+
+        for pass in &self.passes {
+            let pass_params = shaders::load_params(&pass.shader)?;
+
+            params::merge(&mut params, pass_params)
+                .map_err(|e| format!("{}: {}", pass.shader.display(), e))?;
+        }
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            let is_compute = shaders::is_compute_shader(&pass.shader);
+
+            if is_compute && i == image_pass {
+                let shader = pass.shader.display();
+                return Err(format!("{}: the image pass can't be a compute shader", shader));
+            }
+
+            let spirv = shaders::load_shader(&pass.shader, i == image_pass, &params)?;
+            let is_shadertoy = shaders::is_shadertoy_file(&pass.shader);
+
+            let mut channels = [Channel::UNUSED; CHANNEL_COUNT];
+
+            for (channel, source) in channels.iter_mut().zip(&pass.channels) {
+                *channel = match source {
+                    ChannelSource::Unused => Channel::UNUSED,
+                    &ChannelSource::Buffer {
+                        index,
+                        filter,
+                        wrap,
+                    } => Channel {
+                        input: Input::Buffer(index),
+                        filter,
+                        wrap,
+                    },
+                    &ChannelSource::Texture {
+                        ref path,
+                        filter,
+                        wrap,
+                        vflip,
+                    } => {
+                        // Shadertoy shaders expect the bottom row first, like buffers store it
+                        let vflip = vflip.unwrap_or(is_shadertoy);
+                        let mipmaps = filter == Filter::Mipmap;
+                        let index = find_or_load_texture(&mut textures, path, vflip, mipmaps)?;
+
+                        Channel {
+                            input: Input::Texture(index),
+                            filter,
+                            wrap,
+                        }
+                    }
+                };
+            }
+
+            passes.push(CompiledPass {
+                name: pass.name.clone(),
+                spirv,
+                is_compute,
+                channels,
+            });
+        }
+
+        Ok(CompiledProject {
+            passes,
+            textures,
+            params,
+        })
+    }
+
+    /// `shader.frag` keeps its presets in `shader.presets.toml`, projects in a file next to their
+    /// manifest
+    pub fn presets_path(&self) -> PathBuf {
+        match &self.manifest {
+            Some(manifest) => manifest.with_file_name(PRESETS_NAME),
+            None => self.passes[0].shader.with_extension("presets.toml"),
+        }
+    }
+
+    /// Files that the project is built from, not including shader includes
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.manifest.iter().cloned().collect();
+
+        for pass in &self.passes {
+            files.push(pass.shader.clone());
+
+            for channel in &pass.channels {
+                if let ChannelSource::Texture { path, .. } = channel {
+                    files.push(path.clone());
+                }
+            }
+        }
+
+        files
+    }
+}
+
+impl Channel {
+    const UNUSED: Self = Self {
+        input: Input::Unused,
+        filter: Filter::Linear,
+        wrap: Wrap::Clamp,
+    };
+}
+
+fn parse_manifest(path: &Path) -> Result<Vec<PassSource>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+    let manifest: Manifest = toml::from_str(&contents)
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let names: Vec<&str> = manifest.buffer.iter().map(|buffer| buffer.name.as_str()).collect();
+
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(format!("{}: duplicate buffer \"{}\"", path.display(), name));
+        }
+    }
+
+    let resolve = |pass_name: &str, shader: &Path, channels: &[ChannelManifest]| {
+        let channels = parse_channels(channels, &names, dir)
+            .map_err(|message| format!("{}: {}: {}", path.display(), pass_name, message))?;
+
+        Ok::<_, String>(PassSource {
+            name: pass_name.to_string(),
+            shader: dir.join(shader),
+            channels,
+        })
+    };
+
+    let mut passes = Vec::with_capacity(manifest.buffer.len() + 1);
+
+    for buffer in &manifest.buffer {
+        passes.push(resolve(&buffer.name, &buffer.shader, &buffer.channels)?);
+    }
+
+    passes.push(resolve(IMAGE_PASS_NAME, &manifest.image.shader, &manifest.image.channels)?);
+
+    Ok(passes)
+}
+
+/// Buffers default to linear filtering and clamping, textures to mipmaps and repeating like on
+/// Shadertoy
+fn parse_channels(
+    values: &[ChannelManifest],
+    buffers: &[&str],
+    dir: &Path,
+) -> Result<[ChannelSource; CHANNEL_COUNT], String> {
+    if values.len() > CHANNEL_COUNT {
+        return Err(format!("at most {} channels are supported", CHANNEL_COUNT));
+    }
+
+    let find_buffer = |name: &str| {
+        buffers
+            .iter()
+            .position(|buffer| *buffer == name)
+            .ok_or_else(|| format!("unknown buffer \"{}\"", name))
+    };
+
+    let mut channels: [ChannelSource; CHANNEL_COUNT] = Default::default();
+
+    for (channel, value) in channels.iter_mut().zip(values) {
+        *channel = match value {
+            ChannelManifest::Name(name) if name.is_empty() => ChannelSource::Unused,
+            ChannelManifest::Name(name) => ChannelSource::Buffer {
+                index: find_buffer(name)?,
+                filter: Filter::Linear,
+                wrap: Wrap::Clamp,
+            },
+            ChannelManifest::Options(options) => match (&options.buffer, &options.texture) {
+                (Some(name), None) => {
+                    if options.filter == Some(Filter::Mipmap) {
+                        return Err("buffers can't be sampled with mipmaps".to_string());
+                    }
+
+                    if options.vflip.is_some() {
+                        return Err("vflip only applies to textures".to_string());
+                    }
+
+                    ChannelSource::Buffer {
+                        index: find_buffer(name)?,
+                        filter: options.filter.unwrap_or(Filter::Linear),
+                        wrap: options.wrap.unwrap_or(Wrap::Clamp),
+                    }
+                }
+                (None, Some(texture)) => ChannelSource::Texture {
+                    path: dir.join(texture),
+                    filter: options.filter.unwrap_or(Filter::Mipmap),
+                    wrap: options.wrap.unwrap_or(Wrap::Repeat),
+                    vflip: options.vflip,
+                },
+                _ => return Err("channel needs either a buffer or a texture".to_string()),
+            },
+        };
+    }
+
+    Ok(channels)
+}
+
+/// Textures are shared between channels that load them the same way
+fn find_or_load_texture(
+    textures: &mut Vec<Texture>,
+    path: &Path,
+    vflip: bool,
+    mipmaps: bool,
+) -> Result<usize, String> {
+    let existing = textures.iter().position(|texture| {
+        texture.path == path && texture.vflip == vflip && texture.mipmaps == mipmaps
+    });
+
+    if let Some(index) = existing {
+        return Ok(index);
+    }
+
+    textures.push(load_texture(path, vflip, mipmaps)?);
+
+    Ok(textures.len() - 1)
+}
+
+fn load_texture(path: &Path, vflip: bool, mipmaps: bool) -> Result<Texture, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("failed to load {}: {}", path.display(), e))?
+        .to_rgba8();
+
+    let (width, height) = image.dimensions();
+    let mut pixels = image.into_raw();
+
+    if vflip {
+        let row_size = width as usize * 4;
+        pixels = pixels.chunks_exact(row_size).rev().flatten().copied().collect();
+    }
+
+    Ok(Texture {
+        pixels,
+        width,
+        height,
+        mipmaps,
+        path: path.to_path_buf(),
+        vflip,
+    })
+}
@@ -0,0 +1,157 @@
+use crate::params::{self, Param};
+use crate::{project, shadertoy};
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+pub const SHADER_DIR: &str = "shaders";
+pub const DEFAULT_SHADER: &str = "shaders/shader.frag";
+
+/// Loads a fragment or compute shader from disk, compiling it with `glslc` unless it is already
+/// SPIR-V. Shadertoy shaders are detected and wrapped, see `shadertoy::is_shadertoy`.
+/// `is_image_pass` is whether the shader renders the final image rather than a buffer. GLSL gets
+/// a uniform block declaring `params`, SPIR-V has to declare it itself.
+pub fn load_shader(path: &Path, is_image_pass: bool, params: &[Param]) -> Result<Vec<u8>, String> {
+    if is_spirv(path) {
+        return fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e));
+    }
+
+    compile_glsl(path, is_image_pass, params)
+}
+
+/// Parameters declared in a GLSL shader, see `params::parse`. SPIR-V has none.
+pub fn load_params(path: &Path) -> Result<Vec<Param>, String> {
+    if is_spirv(path) {
+        return Ok(Vec::new());
+    }
+
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+    params::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Compute shaders are told apart by their extension, `.comp` or `.comp.spv`
+pub fn is_compute_shader(path: &Path) -> bool {
+    if is_spirv(path) {
+        return path.file_stem().is_some_and(|stem| has_extension(Path::new(stem), "comp"));
+    }
+
+    has_extension(path, "comp")
+}
+
+/// Whether the file is GLSL meant for Shadertoy, see `shadertoy::is_shadertoy`
+pub fn is_shadertoy_file(path: &Path) -> bool {
+    !is_spirv(path) && fs::read_to_string(path).is_ok_and(|source| shadertoy::is_shadertoy(&source))
+}
+
+fn is_spirv(path: &Path) -> bool {
+    has_extension(path, "spv")
+}
+
+fn compile_glsl(path: &Path, is_image_pass: bool, params: &[Param]) -> Result<Vec<u8>, String> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+
+    let params_block = params::glsl_block(params);
+
+    let mut command = Command::new("glslc");
+    command.arg("-O");
+
+    let input = if is_compute_shader(path) {
+        command.arg("-fshader-stage=comp");
+        insert_after_version(&source, &params_block)
+    } else if shadertoy::is_shadertoy(&source) {
+        command.arg("-fshader-stage=frag");
+        Some(shadertoy::wrap(&source, &params_block, is_image_pass))
+    } else {
+        command.arg("-fshader-stage=frag");
+        insert_after_version(&source, &params_block)
+    };
+
+    let Some(input) = input else {
+        command.arg(path);
+        return run_glslc(command, path, None);
+    };
+
+    // Modified source is passed through stdin, so includes need the original directory
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    command.arg("-I").arg(dir).arg("-");
+
+    run_glslc(command, path, Some(input))
+}
+
+/// Places `block` right after the `#version` directive, which has to come first. Line numbers in
+/// compiler errors still refer to the original source. `None` if there's nothing to insert.
+fn insert_after_version(source: &str, block: &str) -> Option<String> {
+    if block.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let version = lines.iter().position(|line| line.trim_start().starts_with("#version"))?;
+
+    let (head, tail) = lines.split_at(version + 1);
+
+    Some(format!("{}\n{}#line {}\n{}\n", head.join("\n"), block, version + 2, tail.join("\n")))
+}
+
+fn run_glslc(mut command: Command, path: &Path, stdin: Option<String>) -> Result<Vec<u8>, String> {
+    command.args(["-o", "-"]).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    if stdin.is_some() {
+        command.stdin(Stdio::piped());
+    }
+
+    let mut child = command.spawn().map_err(|e| format!("failed to run glslc: {}", e))?;
+
+    if let (Some(input), Some(mut child_stdin)) = (stdin, child.stdin.take()) {
+        child_stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("failed to write to glslc: {}", e))?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| format!("failed to run glslc: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("failed to compile {}:\n{}", path.display(), stderr.trim_end()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// Returns fragment shaders (GLSL sources and SPIR-V binaries) and project directories in `dir`,
+/// sorted by name
+pub fn list(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("failed to read {}: {}", dir.display(), e))?;
+
+    let mut shaders: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| is_fragment_shader(path) || is_project(path))
+        .collect();
+
+    shaders.sort();
+
+    Ok(shaders)
+}
+
+fn is_fragment_shader(path: &Path) -> bool {
+    if has_extension(path, "spv") {
+        return path.file_stem().is_some_and(|stem| has_extension(Path::new(stem), "frag"));
+    }
+
+    has_extension(path, "frag")
+}
+
+fn is_project(path: &Path) -> bool {
+    path.join(project::MANIFEST_NAME).is_file()
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
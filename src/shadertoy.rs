@@ -0,0 +1,74 @@
+/// Declarations placed before a Shadertoy shader. Must match `PushConstants` in state.rs.
+const PRELUDE: &str = "\
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    float time;
+    float res_x;
+    float res_y;
+    float time_delta;
+    vec4 mouse;
+    vec4 date;
+    int frame;
+    float frame_rate;
+} vxtr_constants;
+
+#define iTime vxtr_constants.time
+#define iTimeDelta vxtr_constants.time_delta
+#define iFrame vxtr_constants.frame
+#define iFrameRate vxtr_constants.frame_rate
+#define iMouse vxtr_constants.mouse
+#define iDate vxtr_constants.date
+#define iResolution vec3(vxtr_constants.res_x, vxtr_constants.res_y, 1.0)
+
+layout(set = 0, binding = 0) uniform sampler2D iChannel0;
+layout(set = 0, binding = 1) uniform sampler2D iChannel1;
+layout(set = 0, binding = 2) uniform sampler2D iChannel2;
+layout(set = 0, binding = 3) uniform sampler2D iChannel3;
+
+#define iChannelResolution vec3[4]( \
+    vec3(textureSize(iChannel0, 0), 1.0), \
+    vec3(textureSize(iChannel1, 0), 1.0), \
+    vec3(textureSize(iChannel2, 0), 1.0), \
+    vec3(textureSize(iChannel3, 0), 1.0))
+
+layout(location = 0) out vec4 vxtr_out_color;
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord);
+";
+
+/// Entry point placed after a Shadertoy shader that renders the final image
+const IMAGE_EPILOGUE: &str = "
+void main()
+{
+    // Shadertoy's origin is bottom-left, Vulkan's is top-left
+    vec2 frag_coord = vec2(gl_FragCoord.x, vxtr_constants.res_y - gl_FragCoord.y);
+
+    mainImage(vxtr_out_color, frag_coord);
+}
+";
+
+/// Shadertoy shaders have no `#version` directive and define `mainImage` instead of `main`
+pub fn is_shadertoy(source: &str) -> bool {
+    let has_version = source.lines().any(|line| line.trim_start().starts_with("#version"));
+
+    !has_version && source.contains("mainImage")
+}
+
+/// Entry point placed after a Shadertoy shader that renders into a buffer. Buffers are stored
+/// bottom row first, so that sampling them with Shadertoy coordinates needs no flip.
+const BUFFER_EPILOGUE: &str = "
+void main()
+{
+    mainImage(vxtr_out_color, gl_FragCoord.xy);
+}
+";
+
+/// Wraps a Shadertoy shader so that it compiles as a regular fragment shader. Line numbers in
+/// compiler errors still refer to the original source.
+/// `params_block` declares the shader's parameters, see `params::glsl_block`.
+pub fn wrap(source: &str, params_block: &str, is_image_pass: bool) -> String {
+    let epilogue = if is_image_pass { IMAGE_EPILOGUE } else { BUFFER_EPILOGUE };
+
+    format!("{}{}#line 1\n{}\n{}", PRELUDE, params_block, source, epilogue)
+}
@@ -1,79 +1,254 @@
 use glfw_sys::*;
+use vk::DebugName;
 
-const MAX_FRAMES_IN_FLIGHT: usize = 2;
+use crate::config::RenderConfig;
+use crate::date::{self, DateTime};
+use crate::gpu_timer::GpuTimer;
+use crate::params::ParamValues;
+use crate::passes::{MultiPass, PassLayout};
+use crate::project::CompiledProject;
+
+use std::time::Duration;
+
+const OFFSCREEN_FORMAT: VkFormat = VK_FORMAT_R8G8B8A8_SRGB;
+/// iDate of offscreen renders at time 0. A fixed date, unlike the wall clock that windows use,
+/// makes renders and exports of the same time give the same pixels on every run.
+const OFFSCREEN_START_DATE: Duration = Duration::ZERO;
+
+/// Present modes switched to by `toggle_vsync`, in order of preference
+const VSYNC_PRESENT_MODES: [VkPresentModeKHR; 1] = [VK_PRESENT_MODE_FIFO_KHR];
+/// FIFO relaxed waits for vertical blanks unless a frame is late, so `is_vsync` counts it as vsync
+const NO_VSYNC_PRESENT_MODES: [VkPresentModeKHR; 2] =
+    [VK_PRESENT_MODE_IMMEDIATE_KHR, VK_PRESENT_MODE_MAILBOX_KHR];
 
 pub struct State {
     gfx_queue: vk::Queue,
-    present_queue: vk::Queue,
-    swapchain: vk::Swapchain,
-    image_views: Vec<vk::ImageView>,
+    target: Target,
     render_pass: vk::RenderPass,
-    pipeline_layout: vk::PipelineLayout,
-    pipeline: vk::Pipeline,
-    framebuffers: Vec<vk::Framebuffer>,
+    passes: MultiPass,
+    pass_layout: PassLayout,
+    project: CompiledProject,
+    params: ParamValues,
+    gpu_timer: Option<GpuTimer>,
+    /// Milliseconds each pass took in the latest frame that finished on the GPU
+    gpu_times: Option<Vec<f64>>,
+    /// Timings of every frame since the history was last taken, if it's kept
+    gpu_history: Option<Vec<Vec<f64>>>,
     command_buffers: Vec<vk::CommandBuffer>,
     command_pool: vk::CommandPool,
-    image_available: Vec<vk::Semaphore>,
-    render_finished: Vec<vk::Semaphore>,
     is_rendering: Vec<vk::Fence>,
     vertex_buffer: vk::Buffer,
     index_buffer: vk::Buffer,
     index_count: u32,
     current_frame: usize,
+    frames_in_flight: usize,
     current_time: f64,
+    last_frame_time: f64,
+    frame: i32,
+    /// Cursor position while the left button was last held and where it was pressed. The signs
+    /// that iMouse uses come from `mouse_down` and `mouse_clicked`.
+    mouse: [f32; 4],
+    mouse_down: bool,
+    /// Pressed since the last frame
+    mouse_clicked: bool,
+    cursor: [f32; 2],
+    start_date: Duration,
+    screenshot: Option<Screenshot>,
 
     // Must be last
     device: vk::Device,
     instance: vk::Instance,
 }
 
+/// Where frames end up: presented to a window or read back from an offscreen image
+enum Target {
+    Window(WindowTarget),
+    Offscreen(OffscreenTarget),
+}
+
+struct WindowTarget {
+    present_queue: vk::Queue,
+    /// In order of preference
+    present_modes: Vec<VkPresentModeKHR>,
+    swapchain: vk::Swapchain,
+    image_views: Vec<vk::ImageView>,
+    framebuffers: Vec<vk::Framebuffer>,
+    image_available: Vec<vk::Semaphore>,
+    render_finished: Vec<vk::Semaphore>,
+    screenshot_requested: bool,
+    screenshot_buffer: Option<vk::Buffer>,
+}
+
+struct OffscreenTarget {
+    framebuffer: vk::Framebuffer,
+    #[allow(unused)] // Must outlive the framebuffer
+    image_view: vk::ImageView,
+    image: vk::Image,
+    readback_buffer: vk::Buffer,
+}
+
+/// Destination of a copy recorded after the frame is drawn
+enum Readback<'a> {
+    Image(&'a vk::Image, &'a mut vk::Buffer),
+    Swapchain(&'a vk::Swapchain, &'a mut vk::Buffer),
+}
+
+/// Copy of a presented frame, with pixels in the swapchain format
+pub struct Screenshot {
+    pub pixels: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub format: VkFormat,
+}
+
+/// Layout must match the block in shadertoy.rs. Regular shaders can declare any prefix of it.
 #[allow(unused)] // False positive
+#[repr(C)]
 struct PushConstants {
     time: f32,
     res_x: f32,
     res_y: f32,
+    time_delta: f32,
+    /// Same as Shadertoy's iMouse: xy is the cursor position while the left button is held, zw is
+    /// where it was pressed. z is negated once it's released, w after the first frame.
+    mouse: [f32; 4],
+    /// Year, month starting from 0, day and seconds since midnight, in UTC
+    date: [f32; 4],
+    frame: i32,
+    frame_rate: f32,
 }
 
 impl State {
-    pub fn new(glfw_window: *mut GLFWwindow) -> Self {
-        let instance = vk::Instance::new("vxtr", (1, 0, 0), glfw_window);
-        let device = vk::Device::new(&instance);
-        let gfx_queue = device.get_queue(vk::QueueFamily::Graphics).unwrap();
+    pub fn new(
+        glfw_window: *mut GLFWwindow,
+        project: CompiledProject,
+        config: &RenderConfig,
+    ) -> Result<Self, String> {
+        let instance = vk::Instance::new("vxtr", (1, 0, 0), glfw_window, &config.vk_validation())?;
+        let device = vk::Device::with_selector(&instance, config.device_selector().as_ref())?;
         let present_queue = device.get_queue(vk::QueueFamily::Present).unwrap();
-        let swapchain = device.create_swapchain(&instance, true);
-        let image_views = swapchain.get_image_views();
-        let render_pass = device.create_render_pass(swapchain.format());
-        let pipeline_layout =
-            device.create_pipeline_layout::<PushConstants>(VK_SHADER_STAGE_FRAGMENT_BIT);
+        let present_modes = config.vk_present_modes();
+        let swapchain = device.create_swapchain(&instance, &present_modes, None, true)?;
+        let image_views = swapchain.get_image_views()?;
+        let render_pass =
+            device.create_render_pass(swapchain.format(), VK_IMAGE_LAYOUT_PRESENT_SRC_KHR)?;
+        let framebuffers =
+            device.create_framebuffers(&render_pass, &image_views, swapchain.extent())?;
+
+        // Only on startup, swapchains are recreated with the same modes
+        if let Some(requested) = config.requested_present_mode() {
+            let mode = swapchain.present_mode();
+
+            if requested.to_vk() != mode {
+                eprintln!(
+                    "Present mode \"{}\" is not supported, falling back to \"{}\"",
+                    vk::utils::present_mode_name(requested.to_vk()),
+                    vk::utils::present_mode_name(mode)
+                );
+            }
+        }
 
-        let vert_compiled = include_bytes!("../build/shader.vert.spv");
-        let frag_compiled = include_bytes!("../build/shader.frag.spv");
+        present_queue.set_name("present_queue");
+        name_swapchain_objects(&swapchain, &image_views, &framebuffers);
 
-        let vert_shader = device.create_shader(vert_compiled, vk::ShaderType::Vertex);
-        let frag_shader = device.create_shader(frag_compiled, vk::ShaderType::Fragment);
+        let frames_in_flight = config.frames_in_flight;
+        let mut image_available = Vec::with_capacity(frames_in_flight);
+        let mut render_finished = Vec::with_capacity(frames_in_flight);
 
-        let pipeline = device.create_pipeline(
-            &[vert_shader, frag_shader],
-            &swapchain,
-            &render_pass,
-            &pipeline_layout,
-        );
+        for _ in 0..frames_in_flight {
+            image_available.push(device.create_semaphore()?);
+            render_finished.push(device.create_semaphore()?);
+        }
+
+        name_each(&image_available, "image_available");
+        name_each(&render_finished, "render_finished");
+
+        let target = Target::Window(WindowTarget {
+            present_queue,
+            present_modes,
+            swapchain,
+            image_views,
+            framebuffers,
+            image_available,
+            render_finished,
+            screenshot_requested: false,
+            screenshot_buffer: None,
+        });
+
+        Self::with_target(instance, device, target, render_pass, project, frames_in_flight)
+    }
+
+    /// Renders without a window into an image that can be read back with `render_to_image`
+    pub fn new_offscreen(
+        width: u32,
+        height: u32,
+        project: CompiledProject,
+        config: &RenderConfig,
+    ) -> Result<Self, String> {
+        let instance = vk::Instance::new_headless("vxtr", (1, 0, 0), &config.vk_validation())?;
+        let device = vk::Device::with_selector(&instance, config.device_selector().as_ref())?;
+        let extent = VkExtent2D { width, height };
+
+        let image = device.create_image(
+            extent,
+            OFFSCREEN_FORMAT,
+            VK_IMAGE_USAGE_COLOR_ATTACHMENT_BIT | VK_IMAGE_USAGE_TRANSFER_SRC_BIT,
+        )?;
+        let image_view = device.create_image_view(&image)?;
+        let render_pass =
+            device.create_render_pass(OFFSCREEN_FORMAT, VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL)?;
+        let framebuffer = device.create_framebuffer(&render_pass, &image_view, extent)?;
+
+        let readback_size = u64::from(width) * u64::from(height) * 4;
+        let readback_buffer = device.create_buffer(
+            readback_size,
+            VK_BUFFER_USAGE_TRANSFER_DST_BIT,
+            VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT,
+        )?;
+
+        image.set_name("offscreen_image");
+        image_view.set_name("offscreen_image_view");
+        framebuffer.set_name("offscreen_framebuffer");
+        readback_buffer.set_name("readback_buffer");
+
+        let target = Target::Offscreen(OffscreenTarget {
+            framebuffer,
+            image_view,
+            image,
+            readback_buffer,
+        });
+
+        // Each frame is waited for before the next one is rendered
+        Self::with_target(instance, device, target, render_pass, project, 1)
+    }
 
-        let framebuffers = device.create_framebuffers(&render_pass, &image_views, &swapchain);
-        let command_pool = device.create_command_pool(vk::QueueFamily::Graphics);
+    fn with_target(
+        instance: vk::Instance,
+        device: vk::Device,
+        target: Target,
+        render_pass: vk::RenderPass,
+        project: CompiledProject,
+        frames_in_flight: usize,
+    ) -> Result<Self, String> {
+        let gfx_queue = device.get_queue(vk::QueueFamily::Graphics).unwrap();
+        let pass_layout = PassLayout::new::<PushConstants>(&device)?;
+
+        let command_pool = device.create_command_pool(vk::QueueFamily::Graphics)?;
         // must ensure that these can't outlive command_pool
-        let command_buffers = command_pool.create_command_buffers(MAX_FRAMES_IN_FLIGHT);
+        let command_buffers = command_pool.create_command_buffers(frames_in_flight)?;
 
-        let mut image_available = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
-        let mut render_finished = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
-        let mut is_rendering = Vec::with_capacity(MAX_FRAMES_IN_FLIGHT);
+        let mut is_rendering = Vec::with_capacity(frames_in_flight);
 
-        for _ in 0..MAX_FRAMES_IN_FLIGHT {
-            image_available.push(device.create_semaphore());
-            render_finished.push(device.create_semaphore());
-            is_rendering.push(device.create_fence(true));
+        for _ in 0..frames_in_flight {
+            is_rendering.push(device.create_fence(true)?);
         }
 
+        render_pass.set_name("render_pass");
+        gfx_queue.set_name("gfx_queue");
+        command_pool.set_name("command_pool");
+        name_each(&command_buffers, "command_buffers");
+        name_each(&is_rendering, "is_rendering");
+
         let vertices: [f32; 8] = [-1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0];
 
         let vertex_buffer = device.create_buffer_with_data(
@@ -81,7 +256,7 @@ impl State {
             &gfx_queue,
             VK_BUFFER_USAGE_VERTEX_BUFFER_BIT,
             &vertices,
-        );
+        )?;
 
         let indices: [u16; 6] = [0, 1, 2, 2, 3, 0];
 
@@ -90,129 +265,519 @@ impl State {
             &gfx_queue,
             VK_BUFFER_USAGE_INDEX_BUFFER_BIT,
             &indices,
-        );
+        )?;
+
+        vertex_buffer.set_name("vertex_buffer");
+        index_buffer.set_name("index_buffer");
 
-        Self {
+        let passes = MultiPass::new(
+            &device,
+            &command_pool,
+            &gfx_queue,
+            &pass_layout,
+            &render_pass,
+            target.extent(),
+            &project,
+        )?;
+
+        let gpu_timer = GpuTimer::new(&device, frames_in_flight, project.passes.len())?;
+        let params = ParamValues::new(project.params.clone());
+
+        let start_date = match target {
+            Target::Window(_) => date::unix_now(),
+            Target::Offscreen(_) => OFFSCREEN_START_DATE,
+        };
+
+        Ok(Self {
             instance,
             device,
             gfx_queue,
-            present_queue,
-            swapchain,
-            image_views,
+            target,
             render_pass,
-            pipeline_layout,
-            pipeline,
-            framebuffers,
+            passes,
+            pass_layout,
+            project,
+            params,
+            gpu_timer,
+            gpu_times: None,
+            gpu_history: None,
             vertex_buffer,
             index_buffer,
             index_count: indices.len().try_into().unwrap(),
             command_pool,
             command_buffers,
-            image_available,
-            render_finished,
             is_rendering,
             current_frame: 0,
+            frames_in_flight,
             current_time: 0.0,
-        }
+            last_frame_time: 0.0,
+            frame: 0,
+            mouse: [0.0; 4],
+            mouse_down: false,
+            mouse_clicked: false,
+            cursor: [0.0; 2],
+            start_date,
+            screenshot: None,
+        })
     }
 
-    pub fn present(&mut self) {
+    pub fn present(&mut self) -> Result<(), String> {
         let image_index = {
-            self.is_rendering[self.current_frame].wait();
+            self.is_rendering[self.current_frame].wait()?;
+            self.read_gpu_times()?;
 
             let mut image_index = 0;
+            let target = self.target.as_window_mut();
 
-            if self
-                .swapchain
-                .acquire_next_image(&mut self.image_available[self.current_frame], &mut image_index)
-            {
-                self.recreate_swapchain();
-                return;
+            if target.swapchain.acquire_next_image(
+                &mut target.image_available[self.current_frame],
+                &mut image_index,
+            )? {
+                return self.recreate_swapchain();
             }
 
-            self.is_rendering[self.current_frame].reset();
+            self.is_rendering[self.current_frame].reset()?;
 
             image_index
         };
 
-        self.record_commands_to_buffer(image_index as usize);
+        let target = self.target.as_window_mut();
+
+        // Created here rather than on request so that its size matches the current swapchain
+        if target.screenshot_requested {
+            let extent = target.swapchain.extent();
+            let size = u64::from(extent.width) * u64::from(extent.height) * 4;
+
+            target.screenshot_requested = false;
+            target.screenshot_buffer = Some(self.device.create_buffer(
+                size,
+                VK_BUFFER_USAGE_TRANSFER_DST_BIT,
+                VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT,
+            )?);
+        }
+
+        self.record_commands_to_buffer(image_index as usize)?;
+
+        let target = self.target.as_window();
 
         self.gfx_queue.submit_wait(
             &self.command_buffers[self.current_frame],
             VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
-            &self.image_available[self.current_frame],
-            &self.render_finished[self.current_frame],
+            &target.image_available[self.current_frame],
+            &target.render_finished[self.current_frame],
             &self.is_rendering[self.current_frame],
-        );
+        )?;
 
-        self.present_queue.present(
-            &self.render_finished[self.current_frame],
-            &self.swapchain,
+        let swapchain_changed = target.present_queue.present(
+            &target.render_finished[self.current_frame],
+            &target.swapchain,
             image_index,
-        );
+        )?;
+
+        let target = self.target.as_window_mut();
+
+        if let Some(buffer) = target.screenshot_buffer.take() {
+            self.is_rendering[self.current_frame].wait()?;
 
-        self.current_frame = (self.current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
+            let extent = target.swapchain.extent();
+            let mut pixels = vec![0; extent.width as usize * extent.height as usize * 4];
+
+            buffer.read_from_buffer_memory(&mut pixels)?;
+
+            self.screenshot = Some(Screenshot {
+                pixels,
+                width: extent.width,
+                height: extent.height,
+                format: target.swapchain.format(),
+            });
+        }
+
+        self.current_frame = (self.current_frame + 1) % self.frames_in_flight;
+
+        if swapchain_changed {
+            self.recreate_swapchain()?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies the next presented frame, which can then be retrieved with `take_screenshot`
+    pub fn request_screenshot(&mut self) -> Result<(), String> {
+        let target = self.target.as_window_mut();
+
+        if !target.swapchain.supports_capture() {
+            return Err("swapchain images can't be copied on this device".to_string());
+        }
+
+        target.screenshot_requested = true;
+
+        Ok(())
+    }
+
+    pub fn take_screenshot(&mut self) -> Option<Screenshot> {
+        self.screenshot.take()
+    }
+
+    /// Renders a frame to the offscreen target and returns its pixels as tightly packed RGBA8
+    pub fn render_to_image(&mut self) -> Result<Vec<u8>, String> {
+        self.render_offscreen()?;
+
+        let extent = self.target.extent();
+        let mut pixels = vec![0; extent.width as usize * extent.height as usize * 4];
+
+        self.target.as_offscreen().readback_buffer.read_from_buffer_memory(&mut pixels)?;
+
+        Ok(pixels)
+    }
+
+    /// Renders a frame into the offscreen image and waits for it, without reading the pixels
+    pub fn render_offscreen(&mut self) -> Result<(), String> {
+        self.record_commands_to_buffer(0)?;
+
+        self.gfx_queue.submit(&self.command_buffers[self.current_frame])?;
+        self.gfx_queue.wait_idle()?;
+        self.read_gpu_times()
     }
 
-    fn record_commands_to_buffer(&mut self, image_index: usize) {
+    /// Image pass is last
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.project.passes.iter().map(|pass| pass.name.as_str()).collect()
+    }
+
+    /// Per-pass GPU timings in milliseconds, if the device supports timestamps
+    pub fn gpu_times(&self) -> Option<Vec<(&str, f64)>> {
+        let times = self.gpu_times.as_ref()?;
+        let names = self.project.passes.iter().map(|pass| pass.name.as_str());
+
+        Some(names.zip(times.iter().copied()).collect())
+    }
+
+    /// Starts keeping the GPU timings of every frame, see `take_gpu_history`
+    pub fn keep_gpu_history(&mut self) {
+        self.gpu_history = Some(Vec::new());
+    }
+
+    /// Per-pass GPU timings of the frames that finished since the last call, oldest first.
+    /// Frames still in flight are left for later.
+    pub fn take_gpu_history(&mut self) -> Vec<Vec<f64>> {
+        self.gpu_history.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Must be called once the current frame's fence is signaled, before it's recorded again
+    fn read_gpu_times(&mut self) -> Result<(), String> {
+        let Some(timer) = &mut self.gpu_timer else {
+            return Ok(());
+        };
+
+        if let Some(times) = timer.read(self.current_frame)? {
+            if let Some(history) = &mut self.gpu_history {
+                history.push(times.clone());
+            }
+
+            self.gpu_times = Some(times);
+        }
+
+        Ok(())
+    }
+
+    fn record_commands_to_buffer(&mut self, image_index: usize) -> Result<(), String> {
+        let extent = self.target.extent();
+        let frame = self.frame;
+        let current_frame = self.current_frame;
+        let push_constants = self.next_push_constants(extent);
+        let params = self.params.to_bytes();
         let cmd_buffer = &mut self.command_buffers[self.current_frame];
-        let framebuffer = &self.framebuffers[image_index];
-        let extent = self.swapchain.extent();
 
-        let clear_color = [0.0, 0.0, 0.0, 1.0];
+        let (framebuffer, mut readback) = match &mut self.target {
+            Target::Window(target) => (
+                &target.framebuffers[image_index],
+                target
+                    .screenshot_buffer
+                    .as_mut()
+                    .map(|buffer| Readback::Swapchain(&target.swapchain, buffer)),
+            ),
+            Target::Offscreen(target) => (
+                &target.framebuffer,
+                Some(Readback::Image(&target.image, &mut target.readback_buffer)),
+            ),
+        };
 
         let vertex_buffers = [&self.vertex_buffer];
         let offsets = [0];
 
-        // Truncates after ~97 days
-        #[allow(clippy::cast_possible_truncation)]
-        let time_trunc = self.current_time as f32;
+        cmd_buffer.reset()?;
 
-        cmd_buffer.reset();
+        let draw = |handle: &vk::CommandBufferRecording| {
+            handle.bind_vertex_buffers(&vertex_buffers, &offsets);
+
+            handle.bind_index_buffer(&self.index_buffer, 0, VK_INDEX_TYPE_UINT16);
+
+            handle.draw_indexed(self.index_count);
+        };
 
         cmd_buffer.record(|handle| {
-            let push_constants = PushConstants {
-                time: time_trunc,
-                res_x: vk::utils::u32_to_f32_nowarn(extent.width),
-                res_y: vk::utils::u32_to_f32_nowarn(extent.height),
-            };
+            if let Some(timer) = &mut self.gpu_timer {
+                timer.begin(&handle, current_frame);
+            }
 
-            handle.begin_render_pass(clear_color, &self.render_pass, framebuffer, &self.swapchain);
+            let gpu_timer = self.gpu_timer.as_ref();
 
-            handle.bind_pipeline(VK_PIPELINE_BIND_POINT_GRAPHICS, &self.pipeline);
+            self.passes.record(
+                &handle,
+                &self.pass_layout,
+                frame,
+                &self.render_pass,
+                framebuffer,
+                &push_constants,
+                &params,
+                draw,
+                |handle, pass| {
+                    if let Some(timer) = gpu_timer {
+                        timer.end_interval(handle, current_frame, pass);
+                    }
+                },
+            );
 
-            handle.bind_vertex_buffers(&vertex_buffers, &offsets);
+            let Some(readback) = &mut readback else {
+                return;
+            };
 
-            handle.bind_index_buffer(&self.index_buffer, 0, VK_INDEX_TYPE_UINT16);
+            handle.begin_label("Readback");
+
+            match readback {
+                Readback::Image(image, buffer) => {
+                    handle.pipeline_barrier(
+                        VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
+                        VK_PIPELINE_STAGE_TRANSFER_BIT,
+                        VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT,
+                        VK_ACCESS_TRANSFER_READ_BIT,
+                    );
+
+                    handle.copy_image_to_buffer(
+                        image,
+                        VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+                        buffer,
+                    );
+                }
+                Readback::Swapchain(swapchain, buffer) => {
+                    handle.copy_swapchain_image_to_buffer(swapchain, image_index, buffer);
+                }
+            }
 
-            handle.push_constants(
-                &self.pipeline_layout,
-                VK_SHADER_STAGE_FRAGMENT_BIT,
-                0,
-                &push_constants,
+            handle.pipeline_barrier(
+                VK_PIPELINE_STAGE_TRANSFER_BIT,
+                VK_PIPELINE_STAGE_HOST_BIT,
+                VK_ACCESS_TRANSFER_WRITE_BIT,
+                VK_ACCESS_HOST_READ_BIT,
             );
 
-            handle.draw_indexed(self.index_count);
+            handle.end_label();
+        })?;
 
-            handle.end_render_pass();
-        });
+        Ok(())
     }
 
-    fn recreate_swapchain(&mut self) {
-        self.device.wait_idle();
+    /// Advances the frame counter, so must be called once per rendered frame
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    fn next_push_constants(&mut self, extent: VkExtent2D) -> PushConstants {
+        let time_delta = self.current_time - self.last_frame_time;
+        let frame_rate = if time_delta > 0.0 { 1.0 / time_delta } else { 0.0 };
+
+        // Follows the shader's time rather than the wall clock, so that it advances with exported
+        // frames and stops while paused
+        let date = DateTime::from_unix(
+            self.start_date + Duration::from_secs_f64(self.current_time.max(0.0)),
+        );
+
+        let push_constants = PushConstants {
+            // Truncates after ~97 days
+            time: self.current_time as f32,
+            res_x: vk::utils::u32_to_f32_nowarn(extent.width),
+            res_y: vk::utils::u32_to_f32_nowarn(extent.height),
+            time_delta: time_delta as f32,
+            mouse: self.mouse_push_constant(),
+            date: [
+                date.year as f32,
+                (date.month - 1) as f32,
+                date.day as f32,
+                date.seconds_of_day() as f32,
+            ],
+            frame: self.frame,
+            frame_rate: frame_rate as f32,
+        };
+
+        self.mouse_clicked = false;
+        self.frame = self.frame.wrapping_add(1);
+        self.last_frame_time = self.current_time;
+
+        push_constants
+    }
+
+    /// Switches between waiting for vertical blank and presenting as soon as possible. Returns
+    /// the name of the present mode that ended up being used.
+    pub fn toggle_vsync(&mut self) -> Result<&'static str, String> {
+        let target = self.target.as_window_mut();
+
+        target.present_modes = if is_vsync(target.swapchain.present_mode()) {
+            NO_VSYNC_PRESENT_MODES.to_vec()
+        } else {
+            VSYNC_PRESENT_MODES.to_vec()
+        };
+
+        self.recreate_swapchain()?;
+
+        Ok(vk::utils::present_mode_name(self.target.as_window().swapchain.present_mode()))
+    }
 
-        let swapchain = self.device.create_swapchain(&self.instance, false);
-        let image_views = swapchain.get_image_views();
+    fn recreate_swapchain(&mut self) -> Result<(), String> {
+        self.device.wait_idle()?;
+
+        let old_extent = self.target.extent();
+        let old = self.target.as_window();
+        let swapchain = self.device.create_swapchain(
+            &self.instance,
+            &old.present_modes,
+            Some(&old.swapchain),
+            false,
+        )?;
+        let image_views = swapchain.get_image_views()?;
         let framebuffers =
-            self.device.create_framebuffers(&self.render_pass, &image_views, &swapchain);
+            self.device.create_framebuffers(&self.render_pass, &image_views, swapchain.extent())?;
+
+        name_swapchain_objects(&swapchain, &image_views, &framebuffers);
+
+        let target = self.target.as_window_mut();
+
+        target.swapchain = swapchain;
+        target.image_views = image_views;
+        target.framebuffers = framebuffers;
+
+        // Buffers follow the size of the window, and lose their contents
+        let extent = self.target.extent();
 
-        self.swapchain = swapchain;
-        self.image_views = image_views;
-        self.framebuffers = framebuffers;
+        if (extent.width, extent.height) != (old_extent.width, old_extent.height) {
+            self.recreate_passes()?;
+        }
+
+        Ok(())
     }
 
-    pub fn handle_resize(&mut self, _width: i32, _height: i32) {
-        self.recreate_swapchain();
+    /// Replaces the shaders of all passes while keeping the rest of the state, including time.
+    /// Buffers are cleared. If the new passes can't be created, the old ones keep running.
+    pub fn reload_project(&mut self, project: CompiledProject) -> Result<(), String> {
+        self.device.wait_idle()?;
+
+        let (gpu_timer, passes) = self.create_passes(&project)?;
+
+        self.params.update(project.params.clone());
+        self.project = project;
+        self.replace_passes(gpu_timer, passes);
+
+        Ok(())
+    }
+
+    /// Device must be idle
+    fn recreate_passes(&mut self) -> Result<(), String> {
+        let (gpu_timer, passes) = self.create_passes(&self.project)?;
+
+        self.replace_passes(gpu_timer, passes);
+
+        Ok(())
+    }
+
+    /// Creates the passes of `project` along with a timer for them, without touching the current
+    /// ones, so that a failure leaves them usable
+    fn create_passes(
+        &self,
+        project: &CompiledProject,
+    ) -> Result<(Option<GpuTimer>, MultiPass), String> {
+        // Number of passes may have changed
+        let gpu_timer = GpuTimer::new(&self.device, self.frames_in_flight, project.passes.len())?;
+
+        let passes = MultiPass::new(
+            &self.device,
+            &self.command_pool,
+            &self.gfx_queue,
+            &self.pass_layout,
+            &self.render_pass,
+            self.target.extent(),
+            project,
+        )?;
+
+        Ok((gpu_timer, passes))
+    }
+
+    fn replace_passes(&mut self, gpu_timer: Option<GpuTimer>, passes: MultiPass) {
+        self.gpu_timer = gpu_timer;
+        self.gpu_times = None;
+        self.passes = passes;
+    }
+
+    pub fn params(&self) -> &ParamValues {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut ParamValues {
+        &mut self.params
+    }
+
+    /// Takes the framebuffer size in pixels, not the window size. Does nothing if the swapchain
+    /// already has the new size, e.g. because the resize came from a switch to fullscreen that
+    /// was already handled.
+    pub fn handle_resize(&mut self, width: i32, height: i32) -> Result<(), String> {
+        let extent = self.target.extent();
+        let size = (u32::try_from(width), u32::try_from(height));
+
+        if size != (Ok(extent.width), Ok(extent.height)) {
+            self.recreate_swapchain()?;
+        }
+
+        Ok(())
+    }
+
+    /// Switching between windowed and fullscreen can invalidate the swapchain even when the size
+    /// stays the same
+    pub fn handle_fullscreen_switch(&mut self) -> Result<(), String> {
+        self.recreate_swapchain()
+    }
+
+    /// Takes framebuffer coordinates in pixels with the origin at the top-left. GLFW reports the
+    /// cursor in screen coordinates, which have to be scaled first on high-DPI displays.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn handle_cursor_move(&mut self, x: f64, y: f64) {
+        let height = f64::from(self.target.extent().height);
+
+        // Origin is bottom-left, like in Shadertoy
+        self.cursor = [x as f32, (height - y) as f32];
+
+        if self.mouse_down {
+            self.mouse[0] = self.cursor[0];
+            self.mouse[1] = self.cursor[1];
+        }
+    }
+
+    pub fn handle_mouse_press(&mut self) {
+        let [x, y] = self.cursor;
+
+        self.mouse = [x, y, x, y];
+        self.mouse_down = true;
+        self.mouse_clicked = true;
+    }
+
+    pub fn handle_mouse_release(&mut self) {
+        self.mouse_down = false;
+    }
+
+    /// z is negated once the button is released, w after the frame it was pressed in
+    fn mouse_push_constant(&self) -> [f32; 4] {
+        let [x, y, z, w] = self.mouse;
+
+        [
+            x,
+            y,
+            if self.mouse_down { z } else { -z },
+            if self.mouse_clicked { w } else { -w },
+        ]
     }
 
     pub fn update(&mut self, _dt: f64, t: f64) {
@@ -222,6 +787,60 @@ impl State {
 
 impl Drop for State {
     fn drop(&mut self) {
-        self.device.wait_idle();
+        if let Err(error) = self.device.wait_idle() {
+            eprintln!("Error: {}", error);
+        }
+    }
+}
+
+/// Names objects that there is one of per frame in flight or per swapchain image after their
+/// field, like `image_available[1]`
+fn name_each<T: DebugName>(objects: &[T], name: &str) {
+    for (i, object) in objects.iter().enumerate() {
+        object.set_name(&format!("{}[{}]", name, i));
+    }
+}
+
+fn name_swapchain_objects(
+    swapchain: &vk::Swapchain,
+    image_views: &[vk::ImageView],
+    framebuffers: &[vk::Framebuffer],
+) {
+    swapchain.set_name("swapchain");
+    name_each(image_views, "image_views");
+    name_each(framebuffers, "framebuffers");
+}
+
+fn is_vsync(present_mode: VkPresentModeKHR) -> bool {
+    matches!(present_mode, VK_PRESENT_MODE_FIFO_KHR | VK_PRESENT_MODE_FIFO_RELAXED_KHR)
+}
+
+impl Target {
+    fn extent(&self) -> VkExtent2D {
+        match self {
+            Target::Window(target) => target.swapchain.extent(),
+            Target::Offscreen(target) => target.image.extent(),
+        }
+    }
+
+    fn as_window(&self) -> &WindowTarget {
+        match self {
+            Target::Window(target) => target,
+            Target::Offscreen(_) => panic!("Offscreen target can't be presented"),
+        }
+    }
+
+    fn as_window_mut(&mut self) -> &mut WindowTarget {
+        match self {
+            Target::Window(target) => target,
+            Target::Offscreen(_) => panic!("Offscreen target can't be presented"),
+        }
+    }
+
+    fn as_offscreen(&self) -> &OffscreenTarget {
+        match self {
+            Target::Offscreen(target) => target,
+            Target::Window(_) => panic!("Window target can't be read back"),
+        }
     }
 }
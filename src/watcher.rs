@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Polls files, and everything the shaders among them `#include`, for modifications
+pub struct ShaderWatcher {
+    roots: Vec<PathBuf>,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ShaderWatcher {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        let mut watcher = Self {
+            roots,
+            files: Vec::new(),
+        };
+
+        watcher.rescan();
+
+        watcher
+    }
+
+    /// Returns whether any of the watched files changed since the last call
+    pub fn poll(&mut self) -> bool {
+        let changed = self.files.iter().any(|(path, mtime)| get_mtime(path) != *mtime);
+
+        if changed {
+            self.rescan();
+        }
+
+        changed
+    }
+
+    fn rescan(&mut self) {
+        let mut paths = self.roots.clone();
+
+        for root in &self.roots {
+            collect_includes(root, &mut paths);
+        }
+
+        self.files = paths
+            .into_iter()
+            .map(|path| {
+                let mtime = get_mtime(&path);
+                (path, mtime)
+            })
+            .collect();
+    }
+}
+
+fn get_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn collect_includes(path: &Path, paths: &mut Vec<PathBuf>) {
+    // SPIR-V binaries and unreadable files have nothing to follow
+    let Ok(source) = fs::read_to_string(path) else {
+        return;
+    };
+
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    for line in source.lines() {
+        let Some(included) = parse_include(line) else {
+            continue;
+        };
+
+        let included_path = dir.join(included);
+
+        if !paths.contains(&included_path) {
+            paths.push(included_path.clone());
+            collect_includes(&included_path, paths);
+        }
+    }
+}
+
+fn parse_include(line: &str) -> Option<&str> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start().strip_prefix("include")?;
+    let rest = rest.trim();
+
+    let (open, close) = match rest.chars().next()? {
+        '"' => ('"', '"'),
+        '<' => ('<', '>'),
+        _ => return None,
+    };
+
+    rest.strip_prefix(open)?.split(close).next()
+}
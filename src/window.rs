@@ -3,87 +3,410 @@ use glfw_sys::*;
 use std::ffi::{c_void, CStr, CString};
 use std::ptr;
 
-#[allow(unused)]
 pub struct Window {
     pub running: bool,
-    width: i32,
-    height: i32,
+    resolution: Resolution,
+    monitor_index: Option<usize>,
+    /// Used for fullscreen. Looked up on first use, since windowed mode may not have a monitor.
+    monitor: Option<*mut GLFWmonitor>,
+    /// Position and size to return to when leaving fullscreen
+    windowed_rect: (i32, i32, i32, i32),
     window: *mut GLFWwindow,
     events: Vec<Event>,
 }
 
+#[allow(unused)]
 #[derive(Debug)]
 pub enum Event {
-    KeyPress(Key),
-    KeyRelease(Key),
+    /// The `i32` is the platform-specific scancode, which stays unique to the physical key even
+    /// for keys that are `Key::Unknown`
+    KeyPress(Key, i32, Modifiers),
+    /// Key was held down long enough for the OS to repeat it
+    KeyRepeat(Key, i32, Modifiers),
+    KeyRelease(Key, i32, Modifiers),
+    /// Unicode text input, after keyboard layout and modifiers are applied
+    Char(char),
     WindowResize(i32, i32),
+    /// Size in pixels, which is larger than the window size on high-DPI displays
+    FramebufferResize(i32, i32),
+    /// Cursor position in screen coordinates, relative to the top-left corner of the window
+    CursorMove(f64, f64),
+    MousePress(MouseButton),
+    MouseRelease(MouseButton),
+    /// Horizontal and vertical scroll offsets
+    Scroll(f64, f64),
 }
 
-#[derive(Debug)]
+/// Modifier keys held during a key event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers(i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
 pub enum Key {
+    Space = GLFW_KEY_SPACE,
+    Apostrophe = GLFW_KEY_APOSTROPHE,
+    Comma = GLFW_KEY_COMMA,
+    Minus = GLFW_KEY_MINUS,
+    Period = GLFW_KEY_PERIOD,
+    Slash = GLFW_KEY_SLASH,
+    Num0 = GLFW_KEY_0,
+    Num1 = GLFW_KEY_1,
+    Num2 = GLFW_KEY_2,
+    Num3 = GLFW_KEY_3,
+    Num4 = GLFW_KEY_4,
+    Num5 = GLFW_KEY_5,
+    Num6 = GLFW_KEY_6,
+    Num7 = GLFW_KEY_7,
+    Num8 = GLFW_KEY_8,
+    Num9 = GLFW_KEY_9,
+    Semicolon = GLFW_KEY_SEMICOLON,
+    Equal = GLFW_KEY_EQUAL,
+    A = GLFW_KEY_A,
+    B = GLFW_KEY_B,
+    C = GLFW_KEY_C,
+    D = GLFW_KEY_D,
+    E = GLFW_KEY_E,
+    F = GLFW_KEY_F,
+    G = GLFW_KEY_G,
+    H = GLFW_KEY_H,
+    I = GLFW_KEY_I,
+    J = GLFW_KEY_J,
+    K = GLFW_KEY_K,
+    L = GLFW_KEY_L,
+    M = GLFW_KEY_M,
+    N = GLFW_KEY_N,
+    O = GLFW_KEY_O,
+    P = GLFW_KEY_P,
+    Q = GLFW_KEY_Q,
+    R = GLFW_KEY_R,
+    S = GLFW_KEY_S,
+    T = GLFW_KEY_T,
+    U = GLFW_KEY_U,
+    V = GLFW_KEY_V,
+    W = GLFW_KEY_W,
+    X = GLFW_KEY_X,
+    Y = GLFW_KEY_Y,
+    Z = GLFW_KEY_Z,
+    LeftBracket = GLFW_KEY_LEFT_BRACKET,
+    Backslash = GLFW_KEY_BACKSLASH,
+    RightBracket = GLFW_KEY_RIGHT_BRACKET,
+    GraveAccent = GLFW_KEY_GRAVE_ACCENT,
+    World1 = GLFW_KEY_WORLD_1,
+    World2 = GLFW_KEY_WORLD_2,
     Escape = GLFW_KEY_ESCAPE,
+    Enter = GLFW_KEY_ENTER,
+    Tab = GLFW_KEY_TAB,
+    Backspace = GLFW_KEY_BACKSPACE,
+    Insert = GLFW_KEY_INSERT,
+    Delete = GLFW_KEY_DELETE,
+    Right = GLFW_KEY_RIGHT,
+    Left = GLFW_KEY_LEFT,
+    Down = GLFW_KEY_DOWN,
+    Up = GLFW_KEY_UP,
+    PageUp = GLFW_KEY_PAGE_UP,
+    PageDown = GLFW_KEY_PAGE_DOWN,
+    Home = GLFW_KEY_HOME,
+    End = GLFW_KEY_END,
+    CapsLock = GLFW_KEY_CAPS_LOCK,
+    ScrollLock = GLFW_KEY_SCROLL_LOCK,
+    NumLock = GLFW_KEY_NUM_LOCK,
+    PrintScreen = GLFW_KEY_PRINT_SCREEN,
+    Pause = GLFW_KEY_PAUSE,
+    F1 = GLFW_KEY_F1,
+    F2 = GLFW_KEY_F2,
+    F3 = GLFW_KEY_F3,
+    F4 = GLFW_KEY_F4,
+    F5 = GLFW_KEY_F5,
+    F6 = GLFW_KEY_F6,
+    F7 = GLFW_KEY_F7,
+    F8 = GLFW_KEY_F8,
+    F9 = GLFW_KEY_F9,
+    F10 = GLFW_KEY_F10,
+    F11 = GLFW_KEY_F11,
+    F12 = GLFW_KEY_F12,
+    F13 = GLFW_KEY_F13,
+    F14 = GLFW_KEY_F14,
+    F15 = GLFW_KEY_F15,
+    F16 = GLFW_KEY_F16,
+    F17 = GLFW_KEY_F17,
+    F18 = GLFW_KEY_F18,
+    F19 = GLFW_KEY_F19,
+    F20 = GLFW_KEY_F20,
+    F21 = GLFW_KEY_F21,
+    F22 = GLFW_KEY_F22,
+    F23 = GLFW_KEY_F23,
+    F24 = GLFW_KEY_F24,
+    F25 = GLFW_KEY_F25,
+    Kp0 = GLFW_KEY_KP_0,
+    Kp1 = GLFW_KEY_KP_1,
+    Kp2 = GLFW_KEY_KP_2,
+    Kp3 = GLFW_KEY_KP_3,
+    Kp4 = GLFW_KEY_KP_4,
+    Kp5 = GLFW_KEY_KP_5,
+    Kp6 = GLFW_KEY_KP_6,
+    Kp7 = GLFW_KEY_KP_7,
+    Kp8 = GLFW_KEY_KP_8,
+    Kp9 = GLFW_KEY_KP_9,
+    KpDecimal = GLFW_KEY_KP_DECIMAL,
+    KpDivide = GLFW_KEY_KP_DIVIDE,
+    KpMultiply = GLFW_KEY_KP_MULTIPLY,
+    KpSubtract = GLFW_KEY_KP_SUBTRACT,
+    KpAdd = GLFW_KEY_KP_ADD,
+    KpEnter = GLFW_KEY_KP_ENTER,
+    KpEqual = GLFW_KEY_KP_EQUAL,
+    LeftShift = GLFW_KEY_LEFT_SHIFT,
+    LeftControl = GLFW_KEY_LEFT_CONTROL,
+    LeftAlt = GLFW_KEY_LEFT_ALT,
+    LeftSuper = GLFW_KEY_LEFT_SUPER,
+    RightShift = GLFW_KEY_RIGHT_SHIFT,
+    RightControl = GLFW_KEY_RIGHT_CONTROL,
+    RightAlt = GLFW_KEY_RIGHT_ALT,
+    RightSuper = GLFW_KEY_RIGHT_SUPER,
+    Menu = GLFW_KEY_MENU,
     Unknown = GLFW_KEY_UNKNOWN,
 }
 
-#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum MouseButton {
+    Left = GLFW_MOUSE_BUTTON_LEFT,
+    Right = GLFW_MOUSE_BUTTON_RIGHT,
+    Middle = GLFW_MOUSE_BUTTON_MIDDLE,
+    Unknown = -1,
+}
+
 #[derive(Clone, Copy)]
 pub enum Resolution {
     Windowed(i32, i32),
+    /// Borderless, keeps the current video mode of the monitor
     Fullscreen,
+    /// Exclusive, switches the monitor to the closest video mode
     FullscreenWithRes(i32, i32),
 }
 
 impl Window {
-    pub fn new<T: Into<Vec<u8>>>(resolution: Resolution, title: T) -> Self {
+    /// `monitor_index` is an index into the list of connected monitors, the primary one is used if
+    /// it's `None`. Windowed windows are centered on the given monitor, or placed by the window
+    /// manager without one. `resizable` only affects windowed mode.
+    pub fn new<T: Into<Vec<u8>>>(
+        resolution: Resolution,
+        monitor_index: Option<usize>,
+        resizable: bool,
+        title: T,
+    ) -> Self {
         let title_cstr = CString::new(title).unwrap();
 
-        let (width, height, monitor) = unsafe {
+        unsafe {
             glfwInit();
+        }
 
-            match resolution {
-                Resolution::Windowed(width, height) => {
-                    glfwWindowHint(GLFW_RESIZABLE, GLFW_FALSE);
-                    glfwWindowHint(GLFW_FOCUSED, GLFW_FALSE);
-
-                    (width, height, ptr::null_mut())
-                }
-                Resolution::Fullscreen => {
-                    let monitor = get_monitor();
-                    let vidmode = get_video_mode(monitor);
+        let monitor = match resolution {
+            Resolution::Windowed(..) if monitor_index.is_none() => None,
+            _ => Some(get_monitor(monitor_index)),
+        };
 
-                    (vidmode.width, vidmode.height, monitor)
-                }
-                Resolution::FullscreenWithRes(width, height) => {
-                    let monitor = get_monitor();
+        let (width, height) = match resolution {
+            Resolution::Windowed(width, height) | Resolution::FullscreenWithRes(width, height) => {
+                (width, height)
+            }
+            Resolution::Fullscreen => {
+                let vidmode = get_video_mode(monitor.unwrap());
 
-                    (width, height, monitor)
-                }
+                (vidmode.width, vidmode.height)
             }
         };
 
         let window = unsafe {
             glfwWindowHint(GLFW_CLIENT_API, GLFW_NO_API);
+            glfwWindowHint(GLFW_RESIZABLE, if resizable { GLFW_TRUE } else { GLFW_FALSE });
+
+            if let Resolution::Windowed(..) = resolution {
+                glfwWindowHint(GLFW_FOCUSED, GLFW_FALSE);
+            }
+
+            // Borderless fullscreen is a fullscreen window that matches the current video mode
+            if let Resolution::Fullscreen = resolution {
+                let vidmode = get_video_mode(monitor.unwrap());
 
-            glfwCreateWindow(width, height, title_cstr.as_ptr(), monitor, ptr::null_mut())
+                glfwWindowHint(GLFW_RED_BITS, vidmode.redBits);
+                glfwWindowHint(GLFW_GREEN_BITS, vidmode.greenBits);
+                glfwWindowHint(GLFW_BLUE_BITS, vidmode.blueBits);
+                glfwWindowHint(GLFW_REFRESH_RATE, vidmode.refreshRate);
+            }
+
+            let fullscreen_monitor = match resolution {
+                Resolution::Windowed(..) => ptr::null_mut(),
+                _ => monitor.unwrap(),
+            };
+
+            glfwCreateWindow(
+                width,
+                height,
+                title_cstr.as_ptr(),
+                fullscreen_monitor,
+                ptr::null_mut(),
+            )
         };
 
+        if window.is_null() {
+            glfw_panic("create window");
+        }
+
+        let (mut x, mut y) = (0, 0);
+
+        match (resolution, monitor) {
+            (Resolution::Windowed(..), None) => unsafe {
+                glfwGetWindowPos(window, &mut x, &mut y);
+            },
+            (Resolution::Windowed(..), Some(monitor)) => {
+                (x, y) = centered_position(monitor, width, height);
+
+                unsafe {
+                    glfwSetWindowPos(window, x, y);
+                }
+            }
+            // Where the window goes when leaving fullscreen
+            (_, monitor) => (x, y) = centered_position(monitor.unwrap(), width, height),
+        }
+
         Window {
             running: true,
-            width,
-            height,
+            resolution,
+            monitor_index,
+            monitor,
+            windowed_rect: (x, y, width, height),
             window,
             events: Vec::new(),
         }
     }
 
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// Switches between windowed and fullscreen modes. Produces a resize event if the size of the
+    /// window changed.
+    pub fn set_resolution(&mut self, resolution: Resolution) {
+        unsafe {
+            if let Resolution::Windowed(..) = self.resolution {
+                let (mut x, mut y, mut width, mut height) = (0, 0, 0, 0);
+
+                glfwGetWindowPos(self.window, &mut x, &mut y);
+                glfwGetWindowSize(self.window, &mut width, &mut height);
+
+                self.windowed_rect = (x, y, width, height);
+            }
+
+            match resolution {
+                Resolution::Windowed(width, height) => {
+                    let (x, y, ..) = self.windowed_rect;
+
+                    glfwSetWindowMonitor(
+                        self.window,
+                        ptr::null_mut(),
+                        x,
+                        y,
+                        width,
+                        height,
+                        GLFW_DONT_CARE,
+                    );
+                }
+                Resolution::Fullscreen => {
+                    let monitor = self.monitor();
+                    let vidmode = get_video_mode(monitor);
+
+                    glfwSetWindowMonitor(
+                        self.window,
+                        monitor,
+                        0,
+                        0,
+                        vidmode.width,
+                        vidmode.height,
+                        vidmode.refreshRate,
+                    );
+                }
+                Resolution::FullscreenWithRes(width, height) => {
+                    glfwSetWindowMonitor(
+                        self.window,
+                        self.monitor(),
+                        0,
+                        0,
+                        width,
+                        height,
+                        GLFW_DONT_CARE,
+                    );
+                }
+            }
+        }
+
+        self.resolution = resolution;
+    }
+
+    fn monitor(&mut self) -> *mut GLFWmonitor {
+        *self.monitor.get_or_insert_with(|| get_monitor(self.monitor_index))
+    }
+
+    /// Size the window had before it was last made fullscreen, or its current one
+    pub fn windowed_size(&self) -> (i32, i32) {
+        if let Resolution::Fullscreen | Resolution::FullscreenWithRes(..) = self.resolution {
+            let (_, _, width, height) = self.windowed_rect;
+
+            return (width, height);
+        }
+
+        let (mut width, mut height) = (0, 0);
+
+        unsafe {
+            glfwGetWindowSize(self.window, &mut width, &mut height);
+        }
+
+        (width, height)
+    }
+
+    /// In screen coordinates, relative to the top-left corner of the window. For the position
+    /// before the first `CursorMove` event.
+    pub fn cursor_pos(&self) -> (f64, f64) {
+        let (mut x, mut y) = (0.0, 0.0);
+
+        unsafe {
+            glfwGetCursorPos(self.window, &mut x, &mut y);
+        }
+
+        (x, y)
+    }
+
+    /// Pixels per screen coordinate, for converting cursor positions to framebuffer coordinates.
+    /// The two differ on high-DPI displays.
+    pub fn framebuffer_scale(&self) -> (f64, f64) {
+        let (mut width, mut height) = (0, 0);
+        let (mut fb_width, mut fb_height) = (0, 0);
+
+        unsafe {
+            glfwGetWindowSize(self.window, &mut width, &mut height);
+            glfwGetFramebufferSize(self.window, &mut fb_width, &mut fb_height);
+        }
+
+        // Both are 0 while minimized
+        if width == 0 || height == 0 {
+            return (1.0, 1.0);
+        }
+
+        (f64::from(fb_width) / f64::from(width), f64::from(fb_height) / f64::from(height))
+    }
+
     pub fn set_callbacks(&mut self) {
         let self_ptr = (self as *mut Self).cast::<c_void>();
 
         unsafe {
             glfwSetWindowUserPointer(self.window, self_ptr);
             glfwSetKeyCallback(self.window, Some(key_callback));
+            glfwSetCharCallback(self.window, Some(char_callback));
             glfwSetWindowSizeCallback(self.window, Some(window_size_callback));
+            glfwSetFramebufferSizeCallback(self.window, Some(framebuffer_size_callback));
+            glfwSetCursorPosCallback(self.window, Some(cursor_pos_callback));
+            glfwSetMouseButtonCallback(self.window, Some(mouse_button_callback));
+            glfwSetScrollCallback(self.window, Some(scroll_callback));
         }
     }
 
@@ -132,39 +455,231 @@ impl Drop for Window {
     }
 }
 
+#[allow(unused)]
+impl Modifiers {
+    pub fn shift(self) -> bool {
+        self.0 & GLFW_MOD_SHIFT != 0
+    }
+
+    pub fn control(self) -> bool {
+        self.0 & GLFW_MOD_CONTROL != 0
+    }
+
+    pub fn alt(self) -> bool {
+        self.0 & GLFW_MOD_ALT != 0
+    }
+
+    pub fn super_(self) -> bool {
+        self.0 & GLFW_MOD_SUPER != 0
+    }
+}
+
 impl Key {
+    #[allow(clippy::too_many_lines)]
     fn from_i32(num: i32) -> Self {
         match num {
+            GLFW_KEY_SPACE => Key::Space,
+            GLFW_KEY_APOSTROPHE => Key::Apostrophe,
+            GLFW_KEY_COMMA => Key::Comma,
+            GLFW_KEY_MINUS => Key::Minus,
+            GLFW_KEY_PERIOD => Key::Period,
+            GLFW_KEY_SLASH => Key::Slash,
+            GLFW_KEY_0 => Key::Num0,
+            GLFW_KEY_1 => Key::Num1,
+            GLFW_KEY_2 => Key::Num2,
+            GLFW_KEY_3 => Key::Num3,
+            GLFW_KEY_4 => Key::Num4,
+            GLFW_KEY_5 => Key::Num5,
+            GLFW_KEY_6 => Key::Num6,
+            GLFW_KEY_7 => Key::Num7,
+            GLFW_KEY_8 => Key::Num8,
+            GLFW_KEY_9 => Key::Num9,
+            GLFW_KEY_SEMICOLON => Key::Semicolon,
+            GLFW_KEY_EQUAL => Key::Equal,
+            GLFW_KEY_A => Key::A,
+            GLFW_KEY_B => Key::B,
+            GLFW_KEY_C => Key::C,
+            GLFW_KEY_D => Key::D,
+            GLFW_KEY_E => Key::E,
+            GLFW_KEY_F => Key::F,
+            GLFW_KEY_G => Key::G,
+            GLFW_KEY_H => Key::H,
+            GLFW_KEY_I => Key::I,
+            GLFW_KEY_J => Key::J,
+            GLFW_KEY_K => Key::K,
+            GLFW_KEY_L => Key::L,
+            GLFW_KEY_M => Key::M,
+            GLFW_KEY_N => Key::N,
+            GLFW_KEY_O => Key::O,
+            GLFW_KEY_P => Key::P,
+            GLFW_KEY_Q => Key::Q,
+            GLFW_KEY_R => Key::R,
+            GLFW_KEY_S => Key::S,
+            GLFW_KEY_T => Key::T,
+            GLFW_KEY_U => Key::U,
+            GLFW_KEY_V => Key::V,
+            GLFW_KEY_W => Key::W,
+            GLFW_KEY_X => Key::X,
+            GLFW_KEY_Y => Key::Y,
+            GLFW_KEY_Z => Key::Z,
+            GLFW_KEY_LEFT_BRACKET => Key::LeftBracket,
+            GLFW_KEY_BACKSLASH => Key::Backslash,
+            GLFW_KEY_RIGHT_BRACKET => Key::RightBracket,
+            GLFW_KEY_GRAVE_ACCENT => Key::GraveAccent,
+            GLFW_KEY_WORLD_1 => Key::World1,
+            GLFW_KEY_WORLD_2 => Key::World2,
             GLFW_KEY_ESCAPE => Key::Escape,
+            GLFW_KEY_ENTER => Key::Enter,
+            GLFW_KEY_TAB => Key::Tab,
+            GLFW_KEY_BACKSPACE => Key::Backspace,
+            GLFW_KEY_INSERT => Key::Insert,
+            GLFW_KEY_DELETE => Key::Delete,
+            GLFW_KEY_RIGHT => Key::Right,
+            GLFW_KEY_LEFT => Key::Left,
+            GLFW_KEY_DOWN => Key::Down,
+            GLFW_KEY_UP => Key::Up,
+            GLFW_KEY_PAGE_UP => Key::PageUp,
+            GLFW_KEY_PAGE_DOWN => Key::PageDown,
+            GLFW_KEY_HOME => Key::Home,
+            GLFW_KEY_END => Key::End,
+            GLFW_KEY_CAPS_LOCK => Key::CapsLock,
+            GLFW_KEY_SCROLL_LOCK => Key::ScrollLock,
+            GLFW_KEY_NUM_LOCK => Key::NumLock,
+            GLFW_KEY_PRINT_SCREEN => Key::PrintScreen,
+            GLFW_KEY_PAUSE => Key::Pause,
+            GLFW_KEY_F1 => Key::F1,
+            GLFW_KEY_F2 => Key::F2,
+            GLFW_KEY_F3 => Key::F3,
+            GLFW_KEY_F4 => Key::F4,
+            GLFW_KEY_F5 => Key::F5,
+            GLFW_KEY_F6 => Key::F6,
+            GLFW_KEY_F7 => Key::F7,
+            GLFW_KEY_F8 => Key::F8,
+            GLFW_KEY_F9 => Key::F9,
+            GLFW_KEY_F10 => Key::F10,
+            GLFW_KEY_F11 => Key::F11,
+            GLFW_KEY_F12 => Key::F12,
+            GLFW_KEY_F13 => Key::F13,
+            GLFW_KEY_F14 => Key::F14,
+            GLFW_KEY_F15 => Key::F15,
+            GLFW_KEY_F16 => Key::F16,
+            GLFW_KEY_F17 => Key::F17,
+            GLFW_KEY_F18 => Key::F18,
+            GLFW_KEY_F19 => Key::F19,
+            GLFW_KEY_F20 => Key::F20,
+            GLFW_KEY_F21 => Key::F21,
+            GLFW_KEY_F22 => Key::F22,
+            GLFW_KEY_F23 => Key::F23,
+            GLFW_KEY_F24 => Key::F24,
+            GLFW_KEY_F25 => Key::F25,
+            GLFW_KEY_KP_0 => Key::Kp0,
+            GLFW_KEY_KP_1 => Key::Kp1,
+            GLFW_KEY_KP_2 => Key::Kp2,
+            GLFW_KEY_KP_3 => Key::Kp3,
+            GLFW_KEY_KP_4 => Key::Kp4,
+            GLFW_KEY_KP_5 => Key::Kp5,
+            GLFW_KEY_KP_6 => Key::Kp6,
+            GLFW_KEY_KP_7 => Key::Kp7,
+            GLFW_KEY_KP_8 => Key::Kp8,
+            GLFW_KEY_KP_9 => Key::Kp9,
+            GLFW_KEY_KP_DECIMAL => Key::KpDecimal,
+            GLFW_KEY_KP_DIVIDE => Key::KpDivide,
+            GLFW_KEY_KP_MULTIPLY => Key::KpMultiply,
+            GLFW_KEY_KP_SUBTRACT => Key::KpSubtract,
+            GLFW_KEY_KP_ADD => Key::KpAdd,
+            GLFW_KEY_KP_ENTER => Key::KpEnter,
+            GLFW_KEY_KP_EQUAL => Key::KpEqual,
+            GLFW_KEY_LEFT_SHIFT => Key::LeftShift,
+            GLFW_KEY_LEFT_CONTROL => Key::LeftControl,
+            GLFW_KEY_LEFT_ALT => Key::LeftAlt,
+            GLFW_KEY_LEFT_SUPER => Key::LeftSuper,
+            GLFW_KEY_RIGHT_SHIFT => Key::RightShift,
+            GLFW_KEY_RIGHT_CONTROL => Key::RightControl,
+            GLFW_KEY_RIGHT_ALT => Key::RightAlt,
+            GLFW_KEY_RIGHT_SUPER => Key::RightSuper,
+            GLFW_KEY_MENU => Key::Menu,
             _ => Key::Unknown,
         }
     }
 }
 
+impl MouseButton {
+    fn from_i32(num: i32) -> Self {
+        match num {
+            GLFW_MOUSE_BUTTON_LEFT => MouseButton::Left,
+            GLFW_MOUSE_BUTTON_RIGHT => MouseButton::Right,
+            GLFW_MOUSE_BUTTON_MIDDLE => MouseButton::Middle,
+            _ => MouseButton::Unknown,
+        }
+    }
+}
+
 extern "C" fn key_callback(
     glfw_window: *mut GLFWwindow,
     code: i32,
-    _scancode: i32,
+    scancode: i32,
     action: i32,
-    _mods: i32,
+    mods: i32,
 ) {
     let key = Key::from_i32(code);
+    let mods = Modifiers(mods);
 
-    let event = if action == GLFW_PRESS {
-        Event::KeyPress(key)
-    } else {
-        Event::KeyRelease(key)
+    let event = match action {
+        GLFW_PRESS => Event::KeyPress(key, scancode, mods),
+        GLFW_REPEAT => Event::KeyRepeat(key, scancode, mods),
+        _ => Event::KeyRelease(key, scancode, mods),
     };
 
     push_event_to_window(glfw_window, event);
 }
 
+extern "C" fn char_callback(glfw_window: *mut GLFWwindow, codepoint: u32) {
+    if let Some(ch) = char::from_u32(codepoint) {
+        push_event_to_window(glfw_window, Event::Char(ch));
+    }
+}
+
 extern "C" fn window_size_callback(glfw_window: *mut GLFWwindow, width: i32, height: i32) {
     let event = Event::WindowResize(width, height);
 
     push_event_to_window(glfw_window, event);
 }
 
+extern "C" fn framebuffer_size_callback(glfw_window: *mut GLFWwindow, width: i32, height: i32) {
+    let event = Event::FramebufferResize(width, height);
+
+    push_event_to_window(glfw_window, event);
+}
+
+extern "C" fn cursor_pos_callback(glfw_window: *mut GLFWwindow, x: f64, y: f64) {
+    let event = Event::CursorMove(x, y);
+
+    push_event_to_window(glfw_window, event);
+}
+
+extern "C" fn mouse_button_callback(
+    glfw_window: *mut GLFWwindow,
+    code: i32,
+    action: i32,
+    _mods: i32,
+) {
+    let button = MouseButton::from_i32(code);
+
+    let event = if action == GLFW_PRESS {
+        Event::MousePress(button)
+    } else {
+        Event::MouseRelease(button)
+    };
+
+    push_event_to_window(glfw_window, event);
+}
+
+extern "C" fn scroll_callback(glfw_window: *mut GLFWwindow, x_offset: f64, y_offset: f64) {
+    let event = Event::Scroll(x_offset, y_offset);
+
+    push_event_to_window(glfw_window, event);
+}
+
 fn push_event_to_window(glfw_window: *mut GLFWwindow, event: Event) {
     unsafe {
         let window_ptr = glfwGetWindowUserPointer(glfw_window).cast::<Window>();
@@ -177,7 +692,28 @@ fn push_event_to_window(glfw_window: *mut GLFWwindow, event: Event) {
     }
 }
 
-fn get_monitor() -> *mut GLFWmonitor {
+/// Falls back to the primary monitor if there's no monitor with the given index
+fn get_monitor(index: Option<usize>) -> *mut GLFWmonitor {
+    if let Some(index) = index {
+        let monitors = get_monitors();
+
+        if let Some(&monitor) = monitors.get(index) {
+            return monitor;
+        }
+
+        let names: Vec<String> = monitors
+            .iter()
+            .enumerate()
+            .map(|(i, &monitor)| format!("{}: {}", i, get_monitor_name(monitor)))
+            .collect();
+
+        eprintln!(
+            "Monitor {} not found, using the primary one. Connected monitors: {}",
+            index,
+            names.join(", ")
+        );
+    }
+
     let monitor = unsafe { glfwGetPrimaryMonitor() };
 
     if monitor.is_null() {
@@ -187,6 +723,44 @@ fn get_monitor() -> *mut GLFWmonitor {
     monitor
 }
 
+/// The primary monitor is always first
+fn get_monitors() -> Vec<*mut GLFWmonitor> {
+    unsafe {
+        let mut count = 0;
+        let monitors = glfwGetMonitors(&mut count);
+
+        if monitors.is_null() {
+            return Vec::new();
+        }
+
+        std::slice::from_raw_parts(monitors, count.try_into().unwrap()).to_vec()
+    }
+}
+
+fn get_monitor_name(monitor: *mut GLFWmonitor) -> String {
+    unsafe {
+        let name = glfwGetMonitorName(monitor);
+
+        if name.is_null() {
+            return String::from("unknown");
+        }
+
+        CStr::from_ptr(name).to_string_lossy().into_owned()
+    }
+}
+
+/// Top-left corner of a window of the given size in the middle of `monitor`
+fn centered_position(monitor: *mut GLFWmonitor, width: i32, height: i32) -> (i32, i32) {
+    let vidmode = get_video_mode(monitor);
+    let (mut x, mut y) = (0, 0);
+
+    unsafe {
+        glfwGetMonitorPos(monitor, &mut x, &mut y);
+    }
+
+    (x + (vidmode.width - width) / 2, y + (vidmode.height - height) / 2)
+}
+
 fn get_video_mode(monitor: *mut GLFWmonitor) -> GLFWvidmode {
     let vidmode_ptr = unsafe { glfwGetVideoMode(monitor) };
 
@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+
+/// Writes RGBA8 frames as an uncompressed YUV4MPEG2 stream with 4:4:4 BT.601 limited-range chroma
+pub struct Y4mWriter<W: Write> {
+    out: W,
+    planes: Vec<u8>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(mut out: W, width: u32, height: u32, fps: u32) -> io::Result<Self> {
+        writeln!(out, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444", width, height, fps)?;
+
+        Ok(Self {
+            out,
+            planes: Vec::new(),
+        })
+    }
+
+    pub fn write_frame(&mut self, rgba: &[u8]) -> io::Result<()> {
+        let pixel_count = rgba.len() / 4;
+
+        self.planes.clear();
+        self.planes.resize(pixel_count * 3, 0);
+
+        let (y_plane, chroma) = self.planes.split_at_mut(pixel_count);
+        let (u_plane, v_plane) = chroma.split_at_mut(pixel_count);
+
+        for (i, pixel) in rgba.chunks_exact(4).enumerate() {
+            let (y, u, v) = rgb_to_ycbcr(pixel[0], pixel[1], pixel[2]);
+
+            y_plane[i] = y;
+            u_plane[i] = u;
+            v_plane[i] = v;
+        }
+
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&self.planes)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rgb_to_ycbcr(r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+    let (r, g, b) = (f32::from(r) / 255.0, f32::from(g) / 255.0, f32::from(b) / 255.0);
+
+    let y = 16.0 + 65.481 * r + 128.553 * g + 24.966 * b;
+    let cb = 128.0 - 37.797 * r - 74.203 * g + 112.0 * b;
+    let cr = 128.0 + 112.0 * r - 93.786 * g - 18.214 * b;
+
+    (y.round() as u8, cb.round() as u8, cr.round() as u8)
+}
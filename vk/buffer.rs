@@ -1,14 +1,14 @@
 use glfw_sys::*;
 
-use crate::utils::CheckVkError;
-use crate::{Buffer, CommandPool, Device, Queue};
+use crate::utils::{find_memory_type, CheckVkError};
+use crate::{Buffer, CommandPool, Device, Error, Queue};
 
 use std::ffi::c_void;
 use std::mem::{size_of, MaybeUninit};
 use std::ptr;
 
 impl Buffer {
-    pub fn new(device: &Device, size: u64, usage: u32, properties: u32) -> Self {
+    pub fn new(device: &Device, size: u64, usage: u32, properties: u32) -> Result<Self, Error> {
         let create_info = VkBufferCreateInfo {
             sType: VK_STRUCTURE_TYPE_BUFFER_CREATE_INFO,
             size,
@@ -20,10 +20,17 @@ impl Buffer {
         let buffer = unsafe {
             let mut buffer = MaybeUninit::<VkBuffer>::uninit();
             vkCreateBuffer(device.as_raw(), &create_info, ptr::null_mut(), buffer.as_mut_ptr())
-                .check_err("create buffer");
+                .check_err("create buffer")?;
             buffer.assume_init()
         };
 
+        // Freeing null memory does nothing, so dropping this on later errors only frees the buffer
+        let mut created = Self {
+            buffer,
+            memory: ptr::null_mut(),
+            device: device.as_raw(),
+        };
+
         let mem_requirements = unsafe {
             let mut requirements = MaybeUninit::<VkMemoryRequirements>::uninit();
             vkGetBufferMemoryRequirements(device.as_raw(), buffer, requirements.as_mut_ptr());
@@ -31,8 +38,7 @@ impl Buffer {
         };
 
         let memory_type =
-            find_memory_type(device.phys_device, mem_requirements.memoryTypeBits, properties)
-                .expect("failed to find appropriate memory type");
+            find_memory_type(device.phys_device, mem_requirements.memoryTypeBits, properties)?;
 
         let alloc_info = VkMemoryAllocateInfo {
             sType: VK_STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO,
@@ -41,24 +47,21 @@ impl Buffer {
             ..Default::default()
         };
 
-        let memory = unsafe {
+        created.memory = unsafe {
             let mut memory = MaybeUninit::<VkDeviceMemory>::uninit();
 
             vkAllocateMemory(device.as_raw(), &alloc_info, ptr::null_mut(), memory.as_mut_ptr())
-                .check_err("allocate memory");
+                .check_err("allocate memory")?;
 
             memory.assume_init()
         };
 
         unsafe {
-            vkBindBufferMemory(device.as_raw(), buffer, memory, 0);
+            vkBindBufferMemory(device.as_raw(), buffer, created.memory, 0)
+                .check_err("bind buffer memory")?;
         }
 
-        Self {
-            buffer,
-            memory,
-            device: device.as_raw(),
-        }
+        Ok(created)
     }
 
     pub fn with_data<T: Copy>(
@@ -67,7 +70,7 @@ impl Buffer {
         queue: &Queue,
         usage: u32,
         data: &[T],
-    ) -> Self {
+    ) -> Result<Self, Error> {
         let size_bytes: u64 = (data.len() * size_of::<T>()).try_into().unwrap();
 
         let mut staging_buffer = Self::new(
@@ -75,23 +78,23 @@ impl Buffer {
             size_bytes,
             VK_BUFFER_USAGE_TRANSFER_SRC_BIT,
             VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT,
-        );
+        )?;
 
-        staging_buffer.upload_to_buffer_memory(data);
+        staging_buffer.upload_to_buffer_memory(data)?;
 
         let mut buffer = Self::new(
             device,
             size_bytes,
             usage | VK_BUFFER_USAGE_TRANSFER_DST_BIT,
             VK_MEMORY_PROPERTY_DEVICE_LOCAL_BIT,
-        );
+        )?;
 
-        buffer.copy_from_buffer(command_pool, queue, &staging_buffer, size_bytes);
+        buffer.copy_from_buffer(command_pool, queue, &staging_buffer, size_bytes)?;
 
-        buffer
+        Ok(buffer)
     }
 
-    pub fn upload_to_buffer_memory<T: Copy>(&mut self, data: &[T]) {
+    pub fn upload_to_buffer_memory<T: Copy>(&mut self, data: &[T]) -> Result<(), Error> {
         let size_bytes: u64 = (data.len() * size_of::<T>()).try_into().unwrap();
 
         let memory_range = VkMappedMemoryRange {
@@ -107,17 +110,48 @@ impl Buffer {
             let mut void_ptr = ptr.cast::<c_void>();
 
             vkMapMemory(self.device, self.memory, 0, size_bytes, 0, &mut void_ptr)
-                .check_err("map memory");
+                .check_err("map memory")?;
 
             let out_ptr = void_ptr.cast::<T>();
 
             let slice = std::slice::from_raw_parts_mut(out_ptr, data.len());
             slice.copy_from_slice(data);
 
-            vkFlushMappedMemoryRanges(self.device, 1, &memory_range)
-                .check_err("flush mapped memory");
+            let flushed = vkFlushMappedMemoryRanges(self.device, 1, &memory_range);
 
             vkUnmapMemory(self.device, self.memory);
+
+            flushed.check_err("flush mapped memory")
+        }
+    }
+
+    pub fn read_from_buffer_memory<T: Copy>(&self, out: &mut [T]) -> Result<(), Error> {
+        let size_bytes: u64 = (out.len() * size_of::<T>()).try_into().unwrap();
+
+        let memory_range = VkMappedMemoryRange {
+            sType: VK_STRUCTURE_TYPE_MAPPED_MEMORY_RANGE,
+            memory: self.memory,
+            offset: 0,
+            size: VK_WHOLE_SIZE as u64,
+            ..Default::default()
+        };
+
+        unsafe {
+            let mut void_ptr: *mut c_void = ptr::null_mut();
+
+            vkMapMemory(self.device, self.memory, 0, size_bytes, 0, &mut void_ptr)
+                .check_err("map memory")?;
+
+            let invalidated = vkInvalidateMappedMemoryRanges(self.device, 1, &memory_range);
+
+            if invalidated == VK_SUCCESS {
+                let slice = std::slice::from_raw_parts(void_ptr.cast::<T>(), out.len());
+                out.copy_from_slice(slice);
+            }
+
+            vkUnmapMemory(self.device, self.memory);
+
+            invalidated.check_err("invalidate mapped memory")
         }
     }
 
@@ -127,16 +161,16 @@ impl Buffer {
         queue: &Queue,
         src: &Buffer,
         size: u64,
-    ) {
-        let mut cmd_buffer = command_pool.create_command_buffer();
+    ) -> Result<(), Error> {
+        let mut cmd_buffer = command_pool.create_command_buffer()?;
 
         cmd_buffer.record_with_flags(VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT, |handle| {
             handle.copy_buffer_full(src, self, size);
-        });
+        })?;
 
-        queue.submit(&cmd_buffer);
+        queue.submit(&cmd_buffer)?;
 
-        queue.wait_idle();
+        queue.wait_idle()
     }
 }
 
@@ -148,29 +182,3 @@ impl Drop for Buffer {
         }
     }
 }
-
-fn find_memory_type(
-    phys_device: VkPhysicalDevice,
-    req_type: u32,
-    req_properties: u32,
-) -> Option<u32> {
-    let mem_properties = unsafe {
-        let mut properties = MaybeUninit::<VkPhysicalDeviceMemoryProperties>::uninit();
-        vkGetPhysicalDeviceMemoryProperties(phys_device, properties.as_mut_ptr());
-        properties.assume_init()
-    };
-
-    for i in 0..mem_properties.memoryTypeCount {
-        if req_type & (1 << i) == 0 {
-            continue;
-        }
-
-        if mem_properties.memoryTypes[i as usize].propertyFlags & req_properties == 0 {
-            continue;
-        }
-
-        return Some(i);
-    }
-
-    None
-}
@@ -1,6 +1,6 @@
 use glfw_sys::*;
 
-use crate::utils::CheckVkError;
+use crate::utils::{u32_to_f32_nowarn, CheckVkError};
 use crate::*;
 
 use std::ffi::c_void;
@@ -8,7 +8,7 @@ use std::mem::{size_of, MaybeUninit};
 use std::ptr;
 
 impl CommandPool {
-    pub fn new(device: &Device, queue_family_idx: u32) -> Self {
+    pub fn new(device: &Device, queue_family_idx: u32) -> Result<Self, Error> {
         let create_info = VkCommandPoolCreateInfo {
             sType: VK_STRUCTURE_TYPE_COMMAND_POOL_CREATE_INFO,
             flags: VK_COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER_BIT,
@@ -20,18 +20,19 @@ impl CommandPool {
             let mut pool = MaybeUninit::<VkCommandPool>::uninit();
 
             vkCreateCommandPool(device.as_raw(), &create_info, ptr::null(), pool.as_mut_ptr())
-                .check_err("create command pool");
+                .check_err("create command pool")?;
 
             pool.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+            labels: device.labels,
+        })
     }
 
-    pub fn create_command_buffers(&self, count: usize) -> Vec<CommandBuffer> {
+    pub fn create_command_buffers(&self, count: usize) -> Result<Vec<CommandBuffer>, Error> {
         let mut command_buffers = Vec::with_capacity(count);
         command_buffers.resize(count, ptr::null_mut());
 
@@ -45,13 +46,13 @@ impl CommandPool {
 
         unsafe {
             vkAllocateCommandBuffers(self.device, &alloc_info, command_buffers.as_mut_ptr())
-                .check_err("allocate command buffer");
+                .check_err("allocate command buffer")?;
         }
 
-        command_buffers.into_iter().map(|raw| CommandBuffer::new(self, raw)).collect()
+        Ok(command_buffers.into_iter().map(|raw| CommandBuffer::new(self, raw)).collect())
     }
 
-    pub fn create_command_buffer(&self) -> CommandBuffer {
+    pub fn create_command_buffer(&self) -> Result<CommandBuffer, Error> {
         let alloc_info = VkCommandBufferAllocateInfo {
             sType: VK_STRUCTURE_TYPE_COMMAND_BUFFER_ALLOCATE_INFO,
             commandPool: self.raw,
@@ -64,12 +65,12 @@ impl CommandPool {
             let mut cmd_buffer = MaybeUninit::<VkCommandBuffer>::uninit();
 
             vkAllocateCommandBuffers(self.device, &alloc_info, cmd_buffer.as_mut_ptr())
-                .check_err("allocate command buffer");
+                .check_err("allocate command buffer")?;
 
             cmd_buffer.assume_init()
         };
 
-        CommandBuffer::new(self, raw)
+        Ok(CommandBuffer::new(self, raw))
     }
 
     pub fn as_raw(&self) -> VkCommandPool {
@@ -91,16 +92,15 @@ impl CommandBuffer {
             raw,
             device: pool.device,
             cmd_pool: pool.raw,
+            labels: pool.labels,
         }
     }
 
-    pub fn reset(&mut self) {
-        unsafe {
-            vkResetCommandBuffer(self.raw, 0);
-        }
+    pub fn reset(&mut self) -> Result<(), Error> {
+        unsafe { vkResetCommandBuffer(self.raw, 0).check_err("reset command buffer") }
     }
 
-    pub fn record_with_flags<F>(&mut self, flags: u32, mut closure: F)
+    pub fn record_with_flags<F>(&mut self, flags: u32, mut closure: F) -> Result<(), Error>
     where
         F: FnMut(CommandBufferRecording),
     {
@@ -110,25 +110,23 @@ impl CommandBuffer {
             ..Default::default()
         };
 
-        let handle = CommandBufferRecording::new(self.raw);
+        let handle = CommandBufferRecording::new(self.raw, self.labels);
 
         unsafe {
             vkBeginCommandBuffer(self.raw, &begin_info)
-                .check_err("begin recording to command buffer");
+                .check_err("begin recording to command buffer")?;
         }
 
         closure(handle);
 
-        unsafe {
-            vkEndCommandBuffer(self.raw).check_err("end command buffer recording");
-        }
+        unsafe { vkEndCommandBuffer(self.raw).check_err("end command buffer recording") }
     }
 
-    pub fn record<F>(&mut self, closure: F)
+    pub fn record<F>(&mut self, closure: F) -> Result<(), Error>
     where
         F: FnMut(CommandBufferRecording),
     {
-        self.record_with_flags(0, closure);
+        self.record_with_flags(0, closure)
     }
 
     pub fn as_raw(&self) -> VkCommandBuffer {
@@ -145,8 +143,8 @@ impl Drop for CommandBuffer {
 }
 
 impl CommandBufferRecording {
-    fn new(cmd_buf: VkCommandBuffer) -> Self {
-        Self { cmd_buf }
+    fn new(cmd_buf: VkCommandBuffer, labels: DebugLabels) -> Self {
+        Self { cmd_buf, labels }
     }
 
     pub fn copy_buffer(
@@ -172,12 +170,359 @@ impl CommandBufferRecording {
         self.copy_buffer(src, dst, 0, 0, size);
     }
 
+    /// Writes `data` into `dst` as part of the command buffer. Must be outside of a render pass,
+    /// `data` is at most 65536 bytes and its size a multiple of 4.
+    pub fn update_buffer(&self, dst: &Buffer, offset: u64, data: &[u8]) {
+        unsafe {
+            vkCmdUpdateBuffer(
+                self.cmd_buf,
+                dst.buffer,
+                offset,
+                data.len().try_into().unwrap(),
+                data.as_ptr().cast::<c_void>(),
+            );
+        }
+    }
+
+    /// Global memory barrier between commands before and after it
+    pub fn pipeline_barrier(
+        &self,
+        src_stage: u32,
+        dst_stage: u32,
+        src_access: u32,
+        dst_access: u32,
+    ) {
+        let barrier = VkMemoryBarrier {
+            sType: VK_STRUCTURE_TYPE_MEMORY_BARRIER,
+            srcAccessMask: src_access,
+            dstAccessMask: dst_access,
+            ..Default::default()
+        };
+
+        unsafe {
+            vkCmdPipelineBarrier(
+                self.cmd_buf,
+                src_stage,
+                dst_stage,
+                0,
+                1,
+                &barrier,
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+            );
+        }
+    }
+
+    /// Clears the whole color image, discarding its contents, and leaves it in `final_layout`.
+    /// Everything after the barrier waits for the clear.
+    pub fn clear_image(&self, image: &Image, color: [f32; 4], final_layout: u32) {
+        let to_transfer = create_layout_transition(
+            image.raw,
+            VK_IMAGE_LAYOUT_UNDEFINED,
+            VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+            0,
+            VK_ACCESS_TRANSFER_WRITE_BIT,
+        );
+
+        let to_final = create_layout_transition(
+            image.raw,
+            VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+            final_layout,
+            VK_ACCESS_TRANSFER_WRITE_BIT,
+            VK_ACCESS_MEMORY_READ_BIT | VK_ACCESS_MEMORY_WRITE_BIT,
+        );
+
+        let clear_color = VkClearColorValue { float32: color };
+        let range = to_transfer.subresourceRange;
+
+        self.image_barrier(
+            &to_transfer,
+            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+        );
+
+        unsafe {
+            vkCmdClearColorImage(
+                self.cmd_buf,
+                image.raw,
+                VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+                &clear_color,
+                1,
+                &range,
+            );
+        }
+
+        self.image_barrier(
+            &to_final,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+            VK_PIPELINE_STAGE_ALL_COMMANDS_BIT,
+        );
+    }
+
+    /// Fills mip level 0 of a newly created image from tightly packed rows of `src` and generates
+    /// the rest of its levels by blitting each from the previous one. Every level is left in
+    /// `final_layout`.
+    pub fn copy_buffer_to_image(&self, src: &Buffer, image: &Image, final_layout: u32) {
+        let mut to_transfer = create_layout_transition(
+            image.raw,
+            VK_IMAGE_LAYOUT_UNDEFINED,
+            VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+            0,
+            VK_ACCESS_TRANSFER_WRITE_BIT,
+        );
+        to_transfer.subresourceRange.levelCount = image.mip_levels;
+
+        self.image_barrier(
+            &to_transfer,
+            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+        );
+
+        let region = VkBufferImageCopy {
+            bufferOffset: 0,
+            bufferRowLength: 0,
+            bufferImageHeight: 0,
+            imageSubresource: color_subresource_layers(0),
+            imageOffset: VkOffset3D { x: 0, y: 0, z: 0 },
+            imageExtent: VkExtent3D {
+                width: image.extent.width,
+                height: image.extent.height,
+                depth: 1,
+            },
+        };
+
+        unsafe {
+            vkCmdCopyBufferToImage(
+                self.cmd_buf,
+                src.buffer,
+                image.raw,
+                VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+                1,
+                &region,
+            );
+        }
+
+        self.generate_mipmaps(image, final_layout);
+    }
+
+    /// Expects every level in the transfer destination layout, with level 0 filled
+    fn generate_mipmaps(&self, image: &Image, final_layout: u32) {
+        let mut width = i32::try_from(image.extent.width).unwrap();
+        let mut height = i32::try_from(image.extent.height).unwrap();
+
+        for level in 1..image.mip_levels {
+            let mut to_src = create_layout_transition(
+                image.raw,
+                VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+                VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+                VK_ACCESS_TRANSFER_WRITE_BIT,
+                VK_ACCESS_TRANSFER_READ_BIT,
+            );
+            to_src.subresourceRange.baseMipLevel = level - 1;
+
+            self.image_barrier(
+                &to_src,
+                VK_PIPELINE_STAGE_TRANSFER_BIT,
+                VK_PIPELINE_STAGE_TRANSFER_BIT,
+            );
+
+            let next_width = (width / 2).max(1);
+            let next_height = (height / 2).max(1);
+
+            let blit = VkImageBlit {
+                srcSubresource: color_subresource_layers(level - 1),
+                srcOffsets: [
+                    VkOffset3D { x: 0, y: 0, z: 0 },
+                    VkOffset3D {
+                        x: width,
+                        y: height,
+                        z: 1,
+                    },
+                ],
+                dstSubresource: color_subresource_layers(level),
+                dstOffsets: [
+                    VkOffset3D { x: 0, y: 0, z: 0 },
+                    VkOffset3D {
+                        x: next_width,
+                        y: next_height,
+                        z: 1,
+                    },
+                ],
+            };
+
+            unsafe {
+                vkCmdBlitImage(
+                    self.cmd_buf,
+                    image.raw,
+                    VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+                    image.raw,
+                    VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+                    1,
+                    &blit,
+                    VK_FILTER_LINEAR,
+                );
+            }
+
+            width = next_width;
+            height = next_height;
+        }
+
+        let last_level = image.mip_levels - 1;
+
+        // Every level but the last one was a blit source
+        if last_level > 0 {
+            let mut sources_to_final = create_layout_transition(
+                image.raw,
+                VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+                final_layout,
+                VK_ACCESS_TRANSFER_READ_BIT,
+                VK_ACCESS_MEMORY_READ_BIT,
+            );
+            sources_to_final.subresourceRange.levelCount = last_level;
+
+            self.image_barrier(
+                &sources_to_final,
+                VK_PIPELINE_STAGE_TRANSFER_BIT,
+                VK_PIPELINE_STAGE_ALL_COMMANDS_BIT,
+            );
+        }
+
+        let mut last_to_final = create_layout_transition(
+            image.raw,
+            VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL,
+            final_layout,
+            VK_ACCESS_TRANSFER_WRITE_BIT,
+            VK_ACCESS_MEMORY_READ_BIT,
+        );
+        last_to_final.subresourceRange.baseMipLevel = last_level;
+
+        self.image_barrier(
+            &last_to_final,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+            VK_PIPELINE_STAGE_ALL_COMMANDS_BIT,
+        );
+    }
+
+    /// Transitions every mip level of a color image between layouts
+    #[allow(clippy::too_many_arguments)]
+    pub fn transition_image_layout(
+        &self,
+        image: &Image,
+        old_layout: u32,
+        new_layout: u32,
+        src_stage: u32,
+        dst_stage: u32,
+        src_access: u32,
+        dst_access: u32,
+    ) {
+        let mut barrier =
+            create_layout_transition(image.raw, old_layout, new_layout, src_access, dst_access);
+        barrier.subresourceRange.levelCount = image.mip_levels;
+
+        self.image_barrier(&barrier, src_stage, dst_stage);
+    }
+
+    /// Copies the whole color image into tightly packed rows of `dst`
+    pub fn copy_image_to_buffer(&self, image: &Image, layout: u32, dst: &mut Buffer) {
+        self.copy_raw_image_to_buffer(image.raw, layout, image.extent(), dst);
+    }
+
+    /// Copies a presentable swapchain image, which must have been created with transfer source
+    /// usage. The image is left in the present layout.
+    pub fn copy_swapchain_image_to_buffer(
+        &self,
+        swapchain: &Swapchain,
+        image_index: usize,
+        dst: &mut Buffer,
+    ) {
+        let image = swapchain.images[image_index];
+
+        let to_transfer = create_layout_transition(
+            image,
+            VK_IMAGE_LAYOUT_PRESENT_SRC_KHR,
+            VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+            VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT,
+            VK_ACCESS_TRANSFER_READ_BIT,
+        );
+
+        let to_present = create_layout_transition(
+            image,
+            VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+            VK_IMAGE_LAYOUT_PRESENT_SRC_KHR,
+            VK_ACCESS_TRANSFER_READ_BIT,
+            0,
+        );
+
+        self.image_barrier(
+            &to_transfer,
+            VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+        );
+
+        self.copy_raw_image_to_buffer(
+            image,
+            VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+            swapchain.extent,
+            dst,
+        );
+
+        self.image_barrier(
+            &to_present,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+            VK_PIPELINE_STAGE_BOTTOM_OF_PIPE_BIT,
+        );
+    }
+
+    fn copy_raw_image_to_buffer(
+        &self,
+        image: VkImage,
+        layout: u32,
+        extent: VkExtent2D,
+        dst: &mut Buffer,
+    ) {
+        let region = VkBufferImageCopy {
+            bufferOffset: 0,
+            bufferRowLength: 0,
+            bufferImageHeight: 0,
+            imageSubresource: color_subresource_layers(0),
+            imageOffset: VkOffset3D { x: 0, y: 0, z: 0 },
+            imageExtent: VkExtent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+        };
+
+        unsafe {
+            vkCmdCopyImageToBuffer(self.cmd_buf, image, layout, dst.buffer, 1, &region);
+        }
+    }
+
+    fn image_barrier(&self, barrier: &VkImageMemoryBarrier, src_stage: u32, dst_stage: u32) {
+        unsafe {
+            vkCmdPipelineBarrier(
+                self.cmd_buf,
+                src_stage,
+                dst_stage,
+                0,
+                0,
+                ptr::null(),
+                0,
+                ptr::null(),
+                1,
+                barrier,
+            );
+        }
+    }
+
     pub fn begin_render_pass(
         &self,
         clear_color: [f32; 4],
         render_pass: &RenderPass,
         framebuffer: &Framebuffer,
-        swapchain: &Swapchain,
+        extent: VkExtent2D,
     ) {
         let clear_color_value = VkClearValue {
             color: VkClearColorValue {
@@ -191,7 +536,7 @@ impl CommandBufferRecording {
             framebuffer: framebuffer.as_raw(),
             renderArea: VkRect2D {
                 offset: VkOffset2D { x: 0, y: 0 },
-                extent: swapchain.extent(),
+                extent,
             },
             clearValueCount: 1,
             pClearValues: &clear_color_value,
@@ -203,6 +548,28 @@ impl CommandBufferRecording {
         }
     }
 
+    /// Covers the whole of `extent`, for pipelines with dynamic viewport and scissor
+    pub fn set_viewport_and_scissor(&self, extent: VkExtent2D) {
+        let viewport = VkViewport {
+            x: 0.0,
+            y: 0.0,
+            width: u32_to_f32_nowarn(extent.width),
+            height: u32_to_f32_nowarn(extent.height),
+            minDepth: 0.0,
+            maxDepth: 1.0,
+        };
+
+        let scissor = VkRect2D {
+            offset: VkOffset2D { x: 0, y: 0 },
+            extent,
+        };
+
+        unsafe {
+            vkCmdSetViewport(self.cmd_buf, 0, 1, &viewport);
+            vkCmdSetScissor(self.cmd_buf, 0, 1, &scissor);
+        }
+    }
+
     pub fn end_render_pass(&self) {
         unsafe {
             vkCmdEndRenderPass(self.cmd_buf);
@@ -215,6 +582,38 @@ impl CommandBufferRecording {
         }
     }
 
+    pub fn bind_compute_pipeline(&self, pipeline: &ComputePipeline) {
+        unsafe {
+            vkCmdBindPipeline(self.cmd_buf, VK_PIPELINE_BIND_POINT_COMPUTE, pipeline.raw);
+        }
+    }
+
+    /// Binds consecutive sets starting at set number `first_set`. `dynamic_offsets` has one entry
+    /// per dynamic buffer descriptor in the sets, in order.
+    pub fn bind_descriptor_sets(
+        &self,
+        bind_point: u32,
+        pipeline_layout: &PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &[&DescriptorSet],
+        dynamic_offsets: &[u32],
+    ) {
+        let raw: Vec<VkDescriptorSet> = descriptor_sets.iter().map(|set| set.as_raw()).collect();
+
+        unsafe {
+            vkCmdBindDescriptorSets(
+                self.cmd_buf,
+                bind_point,
+                pipeline_layout.raw,
+                first_set,
+                raw.len().try_into().unwrap(),
+                raw.as_ptr(),
+                dynamic_offsets.len().try_into().unwrap(),
+                dynamic_offsets.as_ptr(),
+            );
+        }
+    }
+
     pub fn bind_vertex_buffers(&self, buffers: &[&Buffer], offsets: &[u64]) {
         let raw: Vec<VkBuffer> = buffers.iter().map(|buf| buf.buffer).collect();
 
@@ -262,4 +661,68 @@ impl CommandBufferRecording {
             vkCmdDrawIndexed(self.cmd_buf, idx_count, 1, 0, 0, 0);
         }
     }
+
+    pub fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        unsafe {
+            vkCmdDispatch(self.cmd_buf, group_count_x, group_count_y, group_count_z);
+        }
+    }
+
+    /// Reads the group counts from a `VkDispatchIndirectCommand` at `offset` in `buffer`, which
+    /// must have been created with indirect buffer usage
+    pub fn dispatch_indirect(&self, buffer: &Buffer, offset: u64) {
+        unsafe {
+            vkCmdDispatchIndirect(self.cmd_buf, buffer.buffer, offset);
+        }
+    }
+
+    /// Must be recorded outside of a render pass
+    pub fn reset_query_pool(&self, pool: &QueryPool) {
+        unsafe {
+            vkCmdResetQueryPool(self.cmd_buf, pool.as_raw(), 0, pool.count());
+        }
+    }
+
+    /// Writes the time at which all previously recorded commands have finished `stage`
+    pub fn write_timestamp(&self, stage: u32, pool: &QueryPool, query: u32) {
+        unsafe {
+            vkCmdWriteTimestamp(self.cmd_buf, stage, pool.as_raw(), query);
+        }
+    }
+}
+
+fn create_layout_transition(
+    image: VkImage,
+    old_layout: u32,
+    new_layout: u32,
+    src_access: u32,
+    dst_access: u32,
+) -> VkImageMemoryBarrier {
+    VkImageMemoryBarrier {
+        sType: VK_STRUCTURE_TYPE_IMAGE_MEMORY_BARRIER,
+        srcAccessMask: src_access,
+        dstAccessMask: dst_access,
+        oldLayout: old_layout,
+        newLayout: new_layout,
+        srcQueueFamilyIndex: VK_QUEUE_FAMILY_IGNORED as u32,
+        dstQueueFamilyIndex: VK_QUEUE_FAMILY_IGNORED as u32,
+        image,
+        subresourceRange: VkImageSubresourceRange {
+            aspectMask: VK_IMAGE_ASPECT_COLOR_BIT,
+            baseMipLevel: 0,
+            levelCount: 1,
+            baseArrayLayer: 0,
+            layerCount: 1,
+        },
+        ..Default::default()
+    }
+}
+
+fn color_subresource_layers(mip_level: u32) -> VkImageSubresourceLayers {
+    VkImageSubresourceLayers {
+        aspectMask: VK_IMAGE_ASPECT_COLOR_BIT,
+        mipLevel: mip_level,
+        baseArrayLayer: 0,
+        layerCount: 1,
+    }
 }
@@ -0,0 +1,283 @@
+use glfw_sys::*;
+
+use crate::utils::CheckVkError;
+use crate::{
+    Buffer, CommandBuffer, CommandBufferRecording, CommandPool, ComputePipeline, DebugLabels,
+    DebugName, DescriptorPool, DescriptorSet, DescriptorSetLayout, Device, Error, ErrorKind, Fence,
+    Framebuffer, Image, ImageView, Pipeline, PipelineLayout, QueryPool, Queue, RenderPass, Sampler,
+    Semaphore, Shader, Swapchain, Validation,
+};
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+
+/// Whether the loader or one of `layers` provides `VK_EXT_debug_utils`
+pub(crate) fn supports_debug_utils(layers: &[CString]) -> Result<bool, Error> {
+    let layer_names = layers.iter().map(|layer| layer.as_ptr());
+
+    for layer_name in std::iter::once(ptr::null()).chain(layer_names) {
+        let supported = get_instance_extensions(layer_name)?.iter().any(|ext| {
+            let name = unsafe { CStr::from_ptr(ext.extensionName.as_ptr()) };
+            name.to_bytes_with_nul() == VK_EXT_DEBUG_UTILS_EXTENSION_NAME.as_slice()
+        });
+
+        if supported {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn get_instance_extensions(layer_name: *const c_char) -> Result<Vec<VkExtensionProperties>, Error> {
+    unsafe {
+        let mut count = 0;
+        vkEnumerateInstanceExtensionProperties(layer_name, &mut count, ptr::null_mut())
+            .check_err("get number of instance extensions")?;
+
+        let mut extensions = Vec::with_capacity(count as usize);
+        extensions.resize(count as usize, VkExtensionProperties::default());
+
+        vkEnumerateInstanceExtensionProperties(layer_name, &mut count, extensions.as_mut_ptr())
+            .check_err("get instance extensions")?;
+
+        Ok(extensions)
+    }
+}
+
+/// `validation` has to outlive the messenger, since the callback reads it
+pub(crate) fn messenger_create_info(validation: &Validation) -> VkDebugUtilsMessengerCreateInfoEXT {
+    let mut severity = VK_DEBUG_UTILS_MESSAGE_SEVERITY_WARNING_BIT_EXT
+        | VK_DEBUG_UTILS_MESSAGE_SEVERITY_ERROR_BIT_EXT;
+
+    if validation.verbose {
+        severity |= VK_DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_SEVERITY_INFO_BIT_EXT;
+    }
+
+    VkDebugUtilsMessengerCreateInfoEXT {
+        sType: VK_STRUCTURE_TYPE_DEBUG_UTILS_MESSENGER_CREATE_INFO_EXT,
+        messageSeverity: severity,
+        messageType: VK_DEBUG_UTILS_MESSAGE_TYPE_GENERAL_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_TYPE_VALIDATION_BIT_EXT
+            | VK_DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE_BIT_EXT,
+        pfnUserCallback: Some(log_message),
+        pUserData: ptr::from_ref(validation).cast_mut().cast(),
+        ..Default::default()
+    }
+}
+
+pub(crate) fn create_messenger(
+    instance: VkInstance,
+    create_info: &VkDebugUtilsMessengerCreateInfoEXT,
+) -> Result<VkDebugUtilsMessengerEXT, Error> {
+    // Extension functions aren't exported by the loader and have to be looked up
+    let create = unsafe {
+        let name = b"vkCreateDebugUtilsMessengerEXT\0";
+        let func = vkGetInstanceProcAddr(instance, name.as_ptr().cast());
+
+        mem::transmute::<PFN_vkVoidFunction, PFN_vkCreateDebugUtilsMessengerEXT>(func)
+    };
+
+    let create = create
+        .ok_or_else(|| Error::new("create debug messenger", ErrorKind::ExtensionNotPresent))?;
+
+    let mut messenger = MaybeUninit::<VkDebugUtilsMessengerEXT>::uninit();
+
+    unsafe {
+        create(instance, create_info, ptr::null(), messenger.as_mut_ptr())
+            .check_err("create debug messenger")?;
+
+        Ok(messenger.assume_init())
+    }
+}
+
+pub(crate) fn destroy_messenger(instance: VkInstance, messenger: VkDebugUtilsMessengerEXT) {
+    unsafe {
+        let name = b"vkDestroyDebugUtilsMessengerEXT\0";
+        let func = vkGetInstanceProcAddr(instance, name.as_ptr().cast());
+        let destroy =
+            mem::transmute::<PFN_vkVoidFunction, PFN_vkDestroyDebugUtilsMessengerEXT>(func);
+
+        if let Some(destroy) = destroy {
+            destroy(instance, messenger, ptr::null());
+        }
+    }
+}
+
+/// Prints messages from the layers. Returning true makes the call that caused the message fail.
+unsafe extern "C" fn log_message(
+    severity: VkDebugUtilsMessageSeverityFlagBitsEXT,
+    types: VkDebugUtilsMessageTypeFlagsEXT,
+    callback_data: *const VkDebugUtilsMessengerCallbackDataEXT,
+    user_data: *mut c_void,
+) -> VkBool32 {
+    let is_error = severity & VK_DEBUG_UTILS_MESSAGE_SEVERITY_ERROR_BIT_EXT != 0;
+
+    let level = if is_error {
+        "error"
+    } else if severity & VK_DEBUG_UTILS_MESSAGE_SEVERITY_WARNING_BIT_EXT != 0 {
+        "warning"
+    } else if severity & VK_DEBUG_UTILS_MESSAGE_SEVERITY_INFO_BIT_EXT != 0 {
+        "info"
+    } else {
+        "verbose"
+    };
+
+    let kind = if types & VK_DEBUG_UTILS_MESSAGE_TYPE_VALIDATION_BIT_EXT != 0 {
+        "validation "
+    } else if types & VK_DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE_BIT_EXT != 0 {
+        "performance "
+    } else {
+        ""
+    };
+
+    let message = (*callback_data).pMessage;
+
+    if message.is_null() {
+        eprintln!("Vulkan {}{}", kind, level);
+    } else {
+        eprintln!("Vulkan {}{}: {}", kind, level, CStr::from_ptr(message).to_string_lossy());
+    }
+
+    let validation = &*user_data.cast::<Validation>();
+
+    VkBool32::from(is_error && validation.fail_on_error)
+}
+
+/// Implements `DebugName` for wrappers with the given object type, handle field and device field
+macro_rules! impl_debug_name {
+    ($($wrapper:ty => $object_type:ident, $handle:ident, $device:ident;)*) => {
+        $(
+            impl DebugName for $wrapper {
+                fn set_name(&self, name: &str) {
+                    set_object_name(self.$device, $object_type, self.$handle as u64, name)
+                }
+            }
+        )*
+    };
+}
+
+impl_debug_name! {
+    Device => VK_OBJECT_TYPE_DEVICE, device, device;
+    Queue => VK_OBJECT_TYPE_QUEUE, raw, device;
+    Swapchain => VK_OBJECT_TYPE_SWAPCHAIN_KHR, raw, device;
+    RenderPass => VK_OBJECT_TYPE_RENDER_PASS, raw, device;
+    Shader => VK_OBJECT_TYPE_SHADER_MODULE, module, device;
+    PipelineLayout => VK_OBJECT_TYPE_PIPELINE_LAYOUT, raw, device;
+    Pipeline => VK_OBJECT_TYPE_PIPELINE, raw, device;
+    ComputePipeline => VK_OBJECT_TYPE_PIPELINE, raw, device;
+    Framebuffer => VK_OBJECT_TYPE_FRAMEBUFFER, raw, device;
+    Image => VK_OBJECT_TYPE_IMAGE, raw, device;
+    ImageView => VK_OBJECT_TYPE_IMAGE_VIEW, raw, device;
+    Sampler => VK_OBJECT_TYPE_SAMPLER, raw, device;
+    DescriptorSetLayout => VK_OBJECT_TYPE_DESCRIPTOR_SET_LAYOUT, raw, device;
+    DescriptorPool => VK_OBJECT_TYPE_DESCRIPTOR_POOL, raw, device;
+    DescriptorSet => VK_OBJECT_TYPE_DESCRIPTOR_SET, raw, device;
+    QueryPool => VK_OBJECT_TYPE_QUERY_POOL, raw, device;
+    CommandPool => VK_OBJECT_TYPE_COMMAND_POOL, raw, device;
+    CommandBuffer => VK_OBJECT_TYPE_COMMAND_BUFFER, raw, device;
+    Semaphore => VK_OBJECT_TYPE_SEMAPHORE, raw, device;
+    Fence => VK_OBJECT_TYPE_FENCE, raw, device;
+    Buffer => VK_OBJECT_TYPE_BUFFER, buffer, device;
+}
+
+fn set_object_name(device: VkDevice, object_type: VkObjectType, handle: u64, name: &str) {
+    let set_name = unsafe {
+        let func = get_device_fn(device, b"vkSetDebugUtilsObjectNameEXT\0");
+
+        mem::transmute::<PFN_vkVoidFunction, PFN_vkSetDebugUtilsObjectNameEXT>(func)
+    };
+
+    let Some(set_name) = set_name else {
+        return;
+    };
+
+    let name = to_cstring(name);
+
+    let name_info = VkDebugUtilsObjectNameInfoEXT {
+        sType: VK_STRUCTURE_TYPE_DEBUG_UTILS_OBJECT_NAME_INFO_EXT,
+        objectType: object_type,
+        objectHandle: handle,
+        pObjectName: name.as_ptr(),
+        ..Default::default()
+    };
+
+    let result = unsafe { set_name(device, &name_info).check_err("set object name") };
+
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+    }
+}
+
+impl DebugLabels {
+    pub(crate) fn load(device: VkDevice) -> Self {
+        unsafe {
+            let begin = get_device_fn(device, b"vkCmdBeginDebugUtilsLabelEXT\0");
+            let end = get_device_fn(device, b"vkCmdEndDebugUtilsLabelEXT\0");
+            let insert = get_device_fn(device, b"vkCmdInsertDebugUtilsLabelEXT\0");
+
+            Self {
+                begin: mem::transmute::<PFN_vkVoidFunction, PFN_vkCmdBeginDebugUtilsLabelEXT>(
+                    begin,
+                ),
+                end: mem::transmute::<PFN_vkVoidFunction, PFN_vkCmdEndDebugUtilsLabelEXT>(end),
+                insert: mem::transmute::<PFN_vkVoidFunction, PFN_vkCmdInsertDebugUtilsLabelEXT>(
+                    insert,
+                ),
+            }
+        }
+    }
+}
+
+impl CommandBufferRecording {
+    /// Starts a region of commands that capture tools and validation messages show as `name`.
+    /// Regions can be nested, and each has to be ended with `end_label`.
+    pub fn begin_label(&self, name: &str) {
+        if let Some(begin) = self.labels.begin {
+            let name = to_cstring(name);
+
+            unsafe {
+                begin(self.cmd_buf, &create_label(&name));
+            }
+        }
+    }
+
+    pub fn end_label(&self) {
+        if let Some(end) = self.labels.end {
+            unsafe {
+                end(self.cmd_buf);
+            }
+        }
+    }
+
+    /// Marks a single point between commands
+    pub fn insert_label(&self, name: &str) {
+        if let Some(insert) = self.labels.insert {
+            let name = to_cstring(name);
+
+            unsafe {
+                insert(self.cmd_buf, &create_label(&name));
+            }
+        }
+    }
+}
+
+/// Names can come from project manifests, so null bytes are replaced rather than panicked on
+fn to_cstring(name: &str) -> CString {
+    CString::new(name.replace('\0', "\u{fffd}")).unwrap()
+}
+
+fn create_label(name: &CStr) -> VkDebugUtilsLabelEXT {
+    VkDebugUtilsLabelEXT {
+        sType: VK_STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT,
+        pLabelName: name.as_ptr(),
+        ..Default::default()
+    }
+}
+
+/// Null if `VK_EXT_debug_utils`, which the function belongs to, isn't enabled
+unsafe fn get_device_fn(device: VkDevice, name: &[u8]) -> PFN_vkVoidFunction {
+    vkGetDeviceProcAddr(device, name.as_ptr().cast())
+}
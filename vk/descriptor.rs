@@ -0,0 +1,235 @@
+use glfw_sys::*;
+
+use crate::utils::CheckVkError;
+use crate::{
+    Buffer, DescriptorBinding, DescriptorPool, DescriptorSet, DescriptorSetLayout, Device, Error,
+    ImageView, Sampler,
+};
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+impl DescriptorSetLayout {
+    pub fn new(device: &Device, bindings: &[DescriptorBinding]) -> Result<Self, Error> {
+        let raw_bindings: Vec<VkDescriptorSetLayoutBinding> = bindings
+            .iter()
+            .map(|binding| VkDescriptorSetLayoutBinding {
+                binding: binding.binding,
+                descriptorType: binding.descriptor_type,
+                descriptorCount: binding.count,
+                stageFlags: binding.stages,
+                pImmutableSamplers: ptr::null(),
+            })
+            .collect();
+
+        let create_info = VkDescriptorSetLayoutCreateInfo {
+            sType: VK_STRUCTURE_TYPE_DESCRIPTOR_SET_LAYOUT_CREATE_INFO,
+            bindingCount: raw_bindings.len().try_into().unwrap(),
+            pBindings: raw_bindings.as_ptr(),
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut layout = MaybeUninit::<VkDescriptorSetLayout>::uninit();
+
+            vkCreateDescriptorSetLayout(
+                device.as_raw(),
+                &create_info,
+                ptr::null(),
+                layout.as_mut_ptr(),
+            )
+            .check_err("create descriptor set layout")?;
+
+            layout.assume_init()
+        };
+
+        Ok(Self {
+            raw,
+            device: device.as_raw(),
+        })
+    }
+
+    pub fn as_raw(&self) -> VkDescriptorSetLayout {
+        self.raw
+    }
+}
+
+impl Drop for DescriptorSetLayout {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroyDescriptorSetLayout(self.device, self.raw, ptr::null());
+        }
+    }
+}
+
+impl DescriptorPool {
+    /// `sizes` are pairs of descriptor type and the total number of such descriptors in all sets
+    pub fn new(device: &Device, max_sets: u32, sizes: &[(u32, u32)]) -> Result<Self, Error> {
+        let pool_sizes: Vec<VkDescriptorPoolSize> = sizes
+            .iter()
+            .map(|&(descriptor_type, count)| VkDescriptorPoolSize {
+                type_: descriptor_type,
+                descriptorCount: count,
+            })
+            .collect();
+
+        let create_info = VkDescriptorPoolCreateInfo {
+            sType: VK_STRUCTURE_TYPE_DESCRIPTOR_POOL_CREATE_INFO,
+            maxSets: max_sets,
+            poolSizeCount: pool_sizes.len().try_into().unwrap(),
+            pPoolSizes: pool_sizes.as_ptr(),
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut pool = MaybeUninit::<VkDescriptorPool>::uninit();
+
+            vkCreateDescriptorPool(device.as_raw(), &create_info, ptr::null(), pool.as_mut_ptr())
+                .check_err("create descriptor pool")?;
+
+            pool.assume_init()
+        };
+
+        Ok(Self {
+            raw,
+            device: device.as_raw(),
+        })
+    }
+
+    /// Allocates one set per layout. Sets are freed together with the pool, so they must not
+    /// outlive it.
+    pub fn allocate_sets(
+        &self,
+        layouts: &[&DescriptorSetLayout],
+    ) -> Result<Vec<DescriptorSet>, Error> {
+        let raw_layouts: Vec<VkDescriptorSetLayout> =
+            layouts.iter().map(|layout| layout.raw).collect();
+
+        let mut sets = vec![ptr::null_mut(); layouts.len()];
+
+        let alloc_info = VkDescriptorSetAllocateInfo {
+            sType: VK_STRUCTURE_TYPE_DESCRIPTOR_SET_ALLOCATE_INFO,
+            descriptorPool: self.raw,
+            descriptorSetCount: raw_layouts.len().try_into().unwrap(),
+            pSetLayouts: raw_layouts.as_ptr(),
+            ..Default::default()
+        };
+
+        unsafe {
+            vkAllocateDescriptorSets(self.device, &alloc_info, sets.as_mut_ptr())
+                .check_err("allocate descriptor sets")?;
+        }
+
+        Ok(sets
+            .into_iter()
+            .map(|raw| DescriptorSet {
+                raw,
+                device: self.device,
+            })
+            .collect())
+    }
+
+    pub fn allocate_set(&self, layout: &DescriptorSetLayout) -> Result<DescriptorSet, Error> {
+        Ok(self.allocate_sets(&[layout])?.pop().unwrap())
+    }
+}
+
+impl Drop for DescriptorPool {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroyDescriptorPool(self.device, self.raw, ptr::null());
+        }
+    }
+}
+
+impl DescriptorSet {
+    /// Writes a uniform or storage buffer. `range` can be `VK_WHOLE_SIZE` to use everything from
+    /// `offset` to the end of the buffer.
+    pub fn write_buffer(
+        &mut self,
+        binding: u32,
+        descriptor_type: VkDescriptorType,
+        buffer: &Buffer,
+        offset: u64,
+        range: u64,
+    ) {
+        let buffer_info = VkDescriptorBufferInfo {
+            buffer: buffer.buffer,
+            offset,
+            range,
+        };
+
+        let write = VkWriteDescriptorSet {
+            pBufferInfo: &buffer_info,
+            ..self.create_write(binding, descriptor_type)
+        };
+
+        self.update(&write);
+    }
+
+    /// Writes a sampled, storage or combined image sampler descriptor. `sampler` is only used by
+    /// the latter.
+    pub fn write_image(
+        &mut self,
+        binding: u32,
+        descriptor_type: VkDescriptorType,
+        image_view: &ImageView,
+        sampler: Option<&Sampler>,
+        image_layout: VkImageLayout,
+    ) {
+        let image_info = VkDescriptorImageInfo {
+            sampler: sampler.map_or(ptr::null_mut(), Sampler::as_raw),
+            imageView: image_view.as_raw(),
+            imageLayout: image_layout,
+        };
+
+        let write = VkWriteDescriptorSet {
+            pImageInfo: &image_info,
+            ..self.create_write(binding, descriptor_type)
+        };
+
+        self.update(&write);
+    }
+
+    pub fn write_combined_image_sampler(
+        &mut self,
+        binding: u32,
+        image_view: &ImageView,
+        sampler: &Sampler,
+        image_layout: VkImageLayout,
+    ) {
+        self.write_image(
+            binding,
+            VK_DESCRIPTOR_TYPE_COMBINED_IMAGE_SAMPLER,
+            image_view,
+            Some(sampler),
+            image_layout,
+        );
+    }
+
+    pub fn as_raw(&self) -> VkDescriptorSet {
+        self.raw
+    }
+
+    fn create_write(
+        &self,
+        binding: u32,
+        descriptor_type: VkDescriptorType,
+    ) -> VkWriteDescriptorSet {
+        VkWriteDescriptorSet {
+            sType: VK_STRUCTURE_TYPE_WRITE_DESCRIPTOR_SET,
+            dstSet: self.raw,
+            dstBinding: binding,
+            dstArrayElement: 0,
+            descriptorCount: 1,
+            descriptorType: descriptor_type,
+            ..Default::default()
+        }
+    }
+
+    fn update(&mut self, write: &VkWriteDescriptorSet) {
+        unsafe {
+            vkUpdateDescriptorSets(self.device, 1, write, 0, ptr::null());
+        }
+    }
+}
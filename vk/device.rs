@@ -1,25 +1,45 @@
 use glfw_sys::*;
 
-use crate::utils::{convert_to_c_ptrs, get_validation_layers, CheckVkError};
+use crate::utils::{convert_to_c_ptrs, CheckVkError};
 use crate::*;
 
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::mem::MaybeUninit;
 use std::ptr;
 
+/// Why a physical device can't be used
+enum Rejection {
+    MissingQueueFamily(&'static str),
+    MissingExtension(String),
+    NoSurfaceFormats,
+    NoPresentModes,
+}
+
 impl Device {
-    pub fn new(instance: &Instance) -> Self {
-        let (phys_device, queue_families, swapchain_support) = get_phys_device(instance);
-        let device = create_logical_device(phys_device, &queue_families);
+    /// Picks the first suitable device, preferring discrete GPUs, then integrated, virtual and
+    /// CPU ones
+    pub fn new(instance: &Instance) -> Result<Self, Error> {
+        Self::with_selector(instance, None)
+    }
+
+    /// Uses the device that `selector` matches, which fails if that one isn't suitable. Without
+    /// a selector, picks the device like `new`.
+    pub fn with_selector(
+        instance: &Instance,
+        selector: Option<&DeviceSelector>,
+    ) -> Result<Self, Error> {
+        let (phys_device, queue_families) = get_phys_device(instance, selector)?;
+        let device = create_logical_device(phys_device, &queue_families, &instance.layers)?;
 
-        println!("Chosen device name: {:?}", get_device_name(phys_device));
+        eprintln!("Chosen device name: {:?}", get_device_name(phys_device));
 
-        Self {
+        Ok(Self {
             phys_device,
             device,
             queue_families,
-            swapchain_support,
-        }
+            labels: DebugLabels::load(device),
+        })
     }
 
     pub fn get_queue(&self, queue_family: QueueFamily) -> Option<Queue> {
@@ -28,69 +48,153 @@ impl Device {
         Some(Queue::new(self, family_idx))
     }
 
-    pub fn create_swapchain(&self, instance: &Instance, verbose: bool) -> Swapchain {
-        Swapchain::from_device(self, instance, verbose)
+    pub fn create_swapchain(
+        &self,
+        instance: &Instance,
+        present_modes: &[VkPresentModeKHR],
+        old_swapchain: Option<&Swapchain>,
+        verbose: bool,
+    ) -> Result<Swapchain, Error> {
+        Swapchain::from_device(self, instance, present_modes, old_swapchain, verbose)
     }
 
-    pub fn create_render_pass(&self, image_format: VkFormat) -> RenderPass {
-        RenderPass::new(self, image_format)
+    pub fn create_render_pass(
+        &self,
+        image_format: VkFormat,
+        final_layout: VkImageLayout,
+    ) -> Result<RenderPass, Error> {
+        RenderPass::new(self, image_format, final_layout)
     }
 
-    pub fn create_pipeline_layout<PushConstT>(&self, push_const_stages: u32) -> PipelineLayout {
-        PipelineLayout::new::<PushConstT>(self, push_const_stages)
+    pub fn create_pipeline_layout<PushConstT>(
+        &self,
+        push_const_stages: u32,
+        set_layouts: &[&DescriptorSetLayout],
+    ) -> Result<PipelineLayout, Error> {
+        PipelineLayout::new::<PushConstT>(self, push_const_stages, set_layouts)
     }
 
-    pub fn create_shader(&self, compiled: &[u8], sh_type: ShaderType) -> Shader {
+    pub fn create_shader(&self, compiled: &[u8], sh_type: ShaderType) -> Result<Shader, Error> {
         Shader::from_bytes(self, compiled, sh_type)
     }
 
     pub fn create_pipeline(
         &self,
         shaders: &[Shader],
-        swapchain: &Swapchain,
         render_pass: &RenderPass,
         pipeline_layout: &PipelineLayout,
-    ) -> Pipeline {
-        Pipeline::new(self, shaders, swapchain, render_pass, pipeline_layout)
+    ) -> Result<Pipeline, Error> {
+        Pipeline::new(self, shaders, render_pass, pipeline_layout)
     }
 
-    fn create_framebuffer(
+    pub fn create_compute_pipeline(
+        &self,
+        shader: &Shader,
+        pipeline_layout: &PipelineLayout,
+    ) -> Result<ComputePipeline, Error> {
+        ComputePipeline::new(self, shader, pipeline_layout)
+    }
+
+    pub fn create_framebuffer(
         &self,
         render_pass: &RenderPass,
         image_view: &ImageView,
-        swapchain: &Swapchain,
-    ) -> Framebuffer {
-        Framebuffer::new(self, render_pass, image_view, swapchain)
+        extent: VkExtent2D,
+    ) -> Result<Framebuffer, Error> {
+        Framebuffer::new(self, render_pass, image_view, extent)
     }
 
     pub fn create_framebuffers(
         &self,
         render_pass: &RenderPass,
         image_views: &[ImageView],
-        swapchain: &Swapchain,
-    ) -> Vec<Framebuffer> {
-        let mut framebuffers = Vec::with_capacity(image_views.len());
+        extent: VkExtent2D,
+    ) -> Result<Vec<Framebuffer>, Error> {
+        image_views
+            .iter()
+            .map(|image_view| self.create_framebuffer(render_pass, image_view, extent))
+            .collect()
+    }
 
-        for image_view in image_views {
-            framebuffers.push(self.create_framebuffer(render_pass, image_view, swapchain));
-        }
+    pub fn create_image(
+        &self,
+        extent: VkExtent2D,
+        format: VkFormat,
+        usage: u32,
+    ) -> Result<Image, Error> {
+        Image::new(self, extent, format, usage)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_image_with_data(
+        &self,
+        command_pool: &CommandPool,
+        queue: &Queue,
+        extent: VkExtent2D,
+        format: VkFormat,
+        usage: u32,
+        final_layout: VkImageLayout,
+        data: &[u8],
+        mipmaps: bool,
+    ) -> Result<Image, Error> {
+        Image::with_data(
+            self,
+            command_pool,
+            queue,
+            extent,
+            format,
+            usage,
+            final_layout,
+            data,
+            mipmaps,
+        )
+    }
+
+    pub fn create_image_view(&self, image: &Image) -> Result<ImageView, Error> {
+        ImageView::new(self, image)
+    }
+
+    pub fn create_sampler(
+        &self,
+        filter: u32,
+        mipmap_mode: u32,
+        address_mode: u32,
+    ) -> Result<Sampler, Error> {
+        Sampler::new(self, filter, mipmap_mode, address_mode)
+    }
+
+    pub fn create_descriptor_set_layout(
+        &self,
+        bindings: &[DescriptorBinding],
+    ) -> Result<DescriptorSetLayout, Error> {
+        DescriptorSetLayout::new(self, bindings)
+    }
+
+    pub fn create_descriptor_pool(
+        &self,
+        max_sets: u32,
+        sizes: &[(u32, u32)],
+    ) -> Result<DescriptorPool, Error> {
+        DescriptorPool::new(self, max_sets, sizes)
+    }
 
-        framebuffers
+    pub fn create_timestamp_query_pool(&self, count: u32) -> Result<QueryPool, Error> {
+        QueryPool::new_timestamps(self, count)
     }
 
-    pub fn create_command_pool(&self, queue_family: QueueFamily) -> CommandPool {
+    pub fn create_command_pool(&self, queue_family: QueueFamily) -> Result<CommandPool, Error> {
         CommandPool::new(self, self.get_idx_of_queue_family(queue_family).unwrap())
     }
 
-    pub fn create_semaphore(&self) -> Semaphore {
+    pub fn create_semaphore(&self) -> Result<Semaphore, Error> {
         Semaphore::new(self)
     }
 
-    pub fn create_fence(&self, signaled: bool) -> Fence {
+    pub fn create_fence(&self, signaled: bool) -> Result<Fence, Error> {
         Fence::new(self, signaled)
     }
 
-    pub fn create_buffer(&self, size: u64, usage: u32, properties: u32) -> Buffer {
+    pub fn create_buffer(&self, size: u64, usage: u32, properties: u32) -> Result<Buffer, Error> {
         Buffer::new(self, size, usage, properties)
     }
 
@@ -100,14 +204,30 @@ impl Device {
         queue: &Queue,
         usage: u32,
         data: &[T],
-    ) -> Buffer {
+    ) -> Result<Buffer, Error> {
         Buffer::with_data(self, command_pool, queue, usage, data)
     }
 
-    pub fn wait_idle(&self) {
-        unsafe {
-            vkDeviceWaitIdle(self.device);
-        }
+    pub fn wait_idle(&self) -> Result<(), Error> {
+        unsafe { vkDeviceWaitIdle(self.device).check_err("wait for device to become idle") }
+    }
+
+    /// Queried anew each time, as the capabilities change along with the window
+    pub(crate) fn swapchain_support(&self, instance: &Instance) -> Result<SwapchainSupport, Error> {
+        let surface = instance.surface().expect("swapchain requires a surface");
+
+        query_swapchain_support(self.phys_device, surface)
+    }
+
+    /// Nanoseconds per timestamp tick
+    pub fn timestamp_period(&self) -> f32 {
+        get_device_properties(self.phys_device).limits.timestampPeriod
+    }
+
+    /// Number of meaningful bits in timestamps written on the graphics queue, 0 if they aren't
+    /// supported
+    pub fn graphics_timestamp_bits(&self) -> u32 {
+        self.queue_families.graphics_timestamp_bits
     }
 
     pub fn as_raw(&self) -> VkDevice {
@@ -134,34 +254,82 @@ impl Drop for Device {
     }
 }
 
-fn get_phys_device(instance: &Instance) -> (VkPhysicalDevice, QueueFamilies, SwapchainSupport) {
+impl DeviceSelector {
+    /// A number is an index, 32 hexadecimal digits with optional dashes are a UUID, and anything
+    /// else is a name
+    pub fn parse(value: &str) -> Self {
+        if let Ok(index) = value.parse() {
+            return Self::Index(index);
+        }
+
+        if let Some(uuid) = parse_uuid(value) {
+            return Self::Uuid(uuid);
+        }
+
+        Self::Name(value.to_string())
+    }
+
+    fn matches(&self, idx: usize, phys_device: VkPhysicalDevice) -> bool {
+        match self {
+            Self::Index(index) => *index == idx,
+            Self::Name(name) => {
+                get_device_name(phys_device).to_lowercase().contains(&name.to_lowercase())
+            }
+            Self::Uuid(uuid) => get_device_uuid(phys_device) == *uuid,
+        }
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingQueueFamily(family) => write!(f, "no {} queue family", family),
+            Self::MissingExtension(name) => write!(f, "missing extension {}", name),
+            Self::NoSurfaceFormats => f.write_str("no surface formats"),
+            Self::NoPresentModes => f.write_str("no present modes"),
+        }
+    }
+}
+
+fn get_phys_device(
+    instance: &Instance,
+    selector: Option<&DeviceSelector>,
+) -> Result<(VkPhysicalDevice, QueueFamilies), Error> {
     let devices = unsafe {
         let mut count = 0;
-        vkEnumeratePhysicalDevices(instance.as_raw(), &mut count, ptr::null_mut());
+        vkEnumeratePhysicalDevices(instance.as_raw(), &mut count, ptr::null_mut())
+            .check_err("get number of physical devices")?;
 
-        assert!(count > 0, "No Vulkan-capable GPU found");
+        if count == 0 {
+            return Err(Error::new("choose physical device", ErrorKind::NoDevice));
+        }
 
         let mut devices = Vec::with_capacity(count as usize);
         devices.resize(count as usize, ptr::null_mut());
 
-        vkEnumeratePhysicalDevices(instance.as_raw(), &mut count, devices.as_mut_ptr());
+        vkEnumeratePhysicalDevices(instance.as_raw(), &mut count, devices.as_mut_ptr())
+            .check_err("get physical devices")?;
 
         devices
     };
 
     print_devices(&devices, false);
 
-    choose_phys_device(&devices, instance.surface)
+    match selector {
+        Some(selector) => select_phys_device(&devices, selector, instance.surface()),
+        None => choose_phys_device(&devices, instance.surface()),
+    }
 }
 
 fn print_devices(phys_devices: &[VkPhysicalDevice], verbose: bool) {
-    println!("Devices:");
+    eprintln!("Devices:");
 
     for (i, phys_device) in phys_devices.iter().enumerate() {
         let properties = get_device_properties(*phys_device);
         let features = get_device_features(*phys_device);
 
         print_device_properties(&properties, i, verbose);
+        eprintln!("\tDevice UUID: {}", format_uuid(&get_device_uuid(*phys_device)));
 
         if verbose {
             print_device_features(&features);
@@ -177,6 +345,56 @@ fn get_device_properties(phys_device: VkPhysicalDevice) -> VkPhysicalDevicePrope
     }
 }
 
+/// Stays the same across driver updates and reboots, unlike the index
+fn get_device_uuid(phys_device: VkPhysicalDevice) -> [u8; 16] {
+    let mut id_properties = VkPhysicalDeviceIDProperties {
+        sType: VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_ID_PROPERTIES,
+        ..Default::default()
+    };
+
+    let mut properties = VkPhysicalDeviceProperties2 {
+        sType: VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2,
+        pNext: ptr::addr_of_mut!(id_properties).cast(),
+        ..Default::default()
+    };
+
+    unsafe {
+        vkGetPhysicalDeviceProperties2(phys_device, &mut properties);
+    }
+
+    id_properties.deviceUUID
+}
+
+/// In the usual 8-4-4-4-12 grouping
+fn format_uuid(uuid: &[u8; 16]) -> String {
+    let hex: Vec<String> = uuid.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex[..4].concat(),
+        hex[4..6].concat(),
+        hex[6..8].concat(),
+        hex[8..10].concat(),
+        hex[10..].concat()
+    )
+}
+
+fn parse_uuid(value: &str) -> Option<[u8; 16]> {
+    let digits: Vec<u8> = value.bytes().filter(|&byte| byte != b'-').collect();
+
+    if digits.len() != 32 || !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+
+    let mut uuid = [0; 16];
+
+    for (byte, pair) in uuid.iter_mut().zip(digits.chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+
+    Some(uuid)
+}
+
 fn get_device_features(phys_device: VkPhysicalDevice) -> VkPhysicalDeviceFeatures {
     unsafe {
         let mut f = MaybeUninit::<VkPhysicalDeviceFeatures>::uninit();
@@ -186,11 +404,11 @@ fn get_device_features(phys_device: VkPhysicalDevice) -> VkPhysicalDeviceFeature
 }
 
 fn print_device_properties(p: &VkPhysicalDeviceProperties, idx: usize, verbose: bool) {
-    println!("Device {} properties:", idx);
-    println!("\tAPI version: {} {:?}", p.apiVersion, get_vk_api_version(p.apiVersion));
-    println!("\tDriver version: {} ({:#x})", p.driverVersion, p.driverVersion);
-    println!("\tVendor ID: {} ({:#x})", p.vendorID, p.vendorID);
-    println!("\tDevice ID: {} ({:#x})", p.deviceID, p.deviceID);
+    eprintln!("Device {} properties:", idx);
+    eprintln!("\tAPI version: {} {:?}", p.apiVersion, get_vk_api_version(p.apiVersion));
+    eprintln!("\tDriver version: {} ({:#x})", p.driverVersion, p.driverVersion);
+    eprintln!("\tVendor ID: {} ({:#x})", p.vendorID, p.vendorID);
+    eprintln!("\tDevice ID: {} ({:#x})", p.deviceID, p.deviceID);
 
     let device_type = match p.deviceType {
         VK_PHYSICAL_DEVICE_TYPE_OTHER => "Other",
@@ -201,18 +419,18 @@ fn print_device_properties(p: &VkPhysicalDeviceProperties, idx: usize, verbose:
         _ => "Unknown",
     };
 
-    println!("\tDevice type: {}", device_type);
+    eprintln!("\tDevice type: {}", device_type);
 
     let name = unsafe { CStr::from_ptr(p.deviceName.as_ptr()) };
 
-    println!("\tDevice name: {:?}", name);
+    eprintln!("\tDevice name: {:?}", name);
 
     if verbose {
         let limits = format!("{:#?}", p.limits);
         let indented = limits.lines().map(|line| "\t".to_owned() + line + "\n").collect::<String>();
 
-        println!("\tLimits:");
-        print!("{}", indented);
+        eprintln!("\tLimits:");
+        eprint!("{}", indented);
     }
 }
 
@@ -226,25 +444,38 @@ fn get_vk_api_version(version: u32) -> (u32, u32, u32, u32) {
 }
 
 fn print_device_features(f: &VkPhysicalDeviceFeatures) {
-    println!("Device features:");
+    eprintln!("Device features:");
 
     let features = format!("{:#?}", f);
     let indented = features.lines().map(|line| "\t".to_owned() + line + "\n").collect::<String>();
 
-    print!("{}", indented);
+    eprint!("{}", indented);
 }
 
-fn choose_phys_device(
+fn select_phys_device(
     phys_devices: &[VkPhysicalDevice],
-    surface: VkSurfaceKHR,
-) -> (VkPhysicalDevice, QueueFamilies, SwapchainSupport) {
-    let mut devices_and_types = Vec::with_capacity(phys_devices.len());
-
-    for dev in phys_devices {
-        let properties = get_device_properties(*dev);
-        devices_and_types.push((*dev, properties.deviceType));
+    selector: &DeviceSelector,
+    surface: Option<VkSurfaceKHR>,
+) -> Result<(VkPhysicalDevice, QueueFamilies), Error> {
+    let idx = (0..phys_devices.len())
+        .find(|&i| selector.matches(i, phys_devices[i]))
+        .ok_or_else(|| Error::new("choose physical device", ErrorKind::DeviceNotFound))?;
+
+    let phys_device = phys_devices[idx];
+
+    match is_device_suitable(phys_device, surface)? {
+        Ok(queue_families) => Ok((phys_device, queue_families)),
+        Err(rejection) => {
+            print_rejection(idx, phys_device, &rejection);
+            Err(Error::new("choose physical device", ErrorKind::NoSuitableDevice))
+        }
     }
+}
 
+fn choose_phys_device(
+    phys_devices: &[VkPhysicalDevice],
+    surface: Option<VkSurfaceKHR>,
+) -> Result<(VkPhysicalDevice, QueueFamilies), Error> {
     let type_priorities = [
         VK_PHYSICAL_DEVICE_TYPE_DISCRETE_GPU,
         VK_PHYSICAL_DEVICE_TYPE_INTEGRATED_GPU,
@@ -254,47 +485,65 @@ fn choose_phys_device(
     ];
 
     for type_ in type_priorities {
-        if let Some(device) = first_device_of_type(&devices_and_types, type_) {
-            if let Some((queue_families, swapchain_support)) = is_device_suitable(device, surface) {
-                return (device, queue_families, swapchain_support);
+        for (i, &device) in phys_devices.iter().enumerate() {
+            if get_device_properties(device).deviceType != type_ {
+                continue;
+            }
+
+            match is_device_suitable(device, surface)? {
+                Ok(queue_families) => return Ok((device, queue_families)),
+                Err(rejection) => print_rejection(i, device, &rejection),
             }
         }
     }
 
-    panic!("No suitable GPU found");
+    Err(Error::new("choose physical device", ErrorKind::NoSuitableDevice))
 }
 
-fn first_device_of_type(
-    dt: &[(VkPhysicalDevice, VkPhysicalDeviceType)],
-    type_predicate: VkPhysicalDeviceType,
-) -> Option<VkPhysicalDevice> {
-    dt.iter().find(|(_, type_)| *type_ == type_predicate).map(|(dev, _)| *dev)
+fn print_rejection(idx: usize, phys_device: VkPhysicalDevice, rejection: &Rejection) {
+    eprintln!("Device {} ({}) is not suitable: {}", idx, get_device_name(phys_device), rejection);
 }
 
+/// Without a surface, only graphics support is required
 fn is_device_suitable(
     phys_device: VkPhysicalDevice,
-    surface: VkSurfaceKHR,
-) -> Option<(QueueFamilies, SwapchainSupport)> {
-    let queue_families = get_queue_families(phys_device, surface);
+    surface: Option<VkSurfaceKHR>,
+) -> Result<Result<QueueFamilies, Rejection>, Error> {
+    let queue_families = get_queue_families(phys_device, surface)?;
 
-    if queue_families.graphics.is_none() || queue_families.present.is_none() {
-        return None;
+    if queue_families.graphics.is_none() {
+        return Ok(Err(Rejection::MissingQueueFamily("graphics and compute")));
     }
 
-    if !supports_required_extensions(phys_device) {
-        return None;
+    let Some(surface) = surface else {
+        return Ok(Ok(queue_families));
+    };
+
+    if queue_families.present.is_none() {
+        return Ok(Err(Rejection::MissingQueueFamily("present")));
     }
 
-    let swapchain_support = query_swapchain_support(phys_device, surface);
+    if let Some(extension) = find_missing_extension(phys_device)? {
+        return Ok(Err(Rejection::MissingExtension(extension)));
+    }
 
-    if swapchain_support.formats.is_empty() || swapchain_support.present_modes.is_empty() {
-        return None;
+    let swapchain_support = query_swapchain_support(phys_device, surface)?;
+
+    if swapchain_support.formats.is_empty() {
+        return Ok(Err(Rejection::NoSurfaceFormats));
+    }
+
+    if swapchain_support.present_modes.is_empty() {
+        return Ok(Err(Rejection::NoPresentModes));
     }
 
-    Some((queue_families, swapchain_support))
+    Ok(Ok(queue_families))
 }
 
-fn get_queue_families(phys_device: VkPhysicalDevice, surface: VkSurfaceKHR) -> QueueFamilies {
+fn get_queue_families(
+    phys_device: VkPhysicalDevice,
+    surface: Option<VkSurfaceKHR>,
+) -> Result<QueueFamilies, Error> {
     let mut families = QueueFamilies::default();
 
     let family_properties = unsafe {
@@ -315,8 +564,13 @@ fn get_queue_families(phys_device: VkPhysicalDevice, surface: VkSurfaceKHR) -> Q
         let idx: u32 = i.try_into().unwrap();
         let opt = Some(idx);
 
-        if f.queueFlags & VK_QUEUE_GRAPHICS_BIT != 0 {
+        // Compute passes are recorded into graphics command buffers. Devices with graphics
+        // support have to have a family that supports both.
+        let graphics_and_compute = VK_QUEUE_GRAPHICS_BIT | VK_QUEUE_COMPUTE_BIT;
+
+        if f.queueFlags & graphics_and_compute == graphics_and_compute {
             families.graphics = opt;
+            families.graphics_timestamp_bits = f.timestampValidBits;
         }
         if f.queueFlags & VK_QUEUE_COMPUTE_BIT != 0 {
             families.compute = opt;
@@ -331,10 +585,14 @@ fn get_queue_families(phys_device: VkPhysicalDevice, surface: VkSurfaceKHR) -> Q
             families.protected = opt;
         }
 
+        let Some(surface) = surface else {
+            continue;
+        };
+
         let mut present_support = 0;
         unsafe {
             vkGetPhysicalDeviceSurfaceSupportKHR(phys_device, idx, surface, &mut present_support)
-                .check_err("get surface presentation support");
+                .check_err("get surface presentation support")?;
         }
 
         if present_support != 0 {
@@ -342,38 +600,38 @@ fn get_queue_families(phys_device: VkPhysicalDevice, surface: VkSurfaceKHR) -> Q
         }
     }
 
-    families
+    Ok(families)
 }
 
 fn print_queue_families(family_properties: &[VkQueueFamilyProperties]) {
-    println!("Queue families:");
+    eprintln!("Queue families:");
 
     for (i, f) in family_properties.iter().enumerate() {
-        println!("{}:", i);
+        eprintln!("{}:", i);
 
-        print!("\tFlags: ");
+        eprint!("\tFlags: ");
 
         if f.queueFlags & VK_QUEUE_GRAPHICS_BIT != 0 {
-            print!("graphics ");
+            eprint!("graphics ");
         }
         if f.queueFlags & VK_QUEUE_COMPUTE_BIT != 0 {
-            print!("compute ");
+            eprint!("compute ");
         }
         if f.queueFlags & VK_QUEUE_TRANSFER_BIT != 0 {
-            print!("transfer ");
+            eprint!("transfer ");
         }
         if f.queueFlags & VK_QUEUE_SPARSE_BINDING_BIT != 0 {
-            print!("sparse_binding ");
+            eprint!("sparse_binding ");
         }
         if f.queueFlags & VK_QUEUE_PROTECTED_BIT != 0 {
-            print!("protected ");
+            eprint!("protected ");
         }
 
-        println!();
+        eprintln!();
 
-        println!("\tCount: {}", f.queueCount);
-        println!("\tTimestamp bits: {}", f.timestampValidBits);
-        println!(
+        eprintln!("\tCount: {}", f.queueCount);
+        eprintln!("\tTimestamp bits: {}", f.timestampValidBits);
+        eprintln!(
             "\tMin image transfer: {}x{}x{}",
             f.minImageTransferGranularity.width,
             f.minImageTransferGranularity.height,
@@ -382,25 +640,19 @@ fn print_queue_families(family_properties: &[VkQueueFamilyProperties]) {
     }
 }
 
-fn supports_required_extensions(phys_device: VkPhysicalDevice) -> bool {
-    let required_extensions = get_required_extensions();
-
-    let mut support_found = Vec::with_capacity(required_extensions.len());
-    support_found.resize(required_extensions.len(), false);
-
-    let supported_extensions = get_supported_extensions(phys_device);
+/// Returns the name of the first required extension that isn't supported
+fn find_missing_extension(phys_device: VkPhysicalDevice) -> Result<Option<String>, Error> {
+    let supported_extensions = get_supported_extensions(phys_device)?;
 
-    for (i, req_ext) in required_extensions.into_iter().enumerate() {
-        for supp_ext in &supported_extensions {
+    let missing = get_required_extensions().into_iter().find(|req_ext| {
+        !supported_extensions.iter().any(|supp_ext| {
             let supp = unsafe { CStr::from_ptr(supp_ext.extensionName.as_ptr()) };
 
-            if supp == req_ext.as_c_str() {
-                support_found[i] = true;
-            }
-        }
-    }
+            supp == req_ext.as_c_str()
+        })
+    });
 
-    support_found.into_iter().all(|found| found)
+    Ok(missing.map(|name| name.to_string_lossy().into_owned()))
 }
 
 fn get_required_extensions() -> Vec<CString> {
@@ -412,11 +664,13 @@ fn get_required_extensions() -> Vec<CString> {
         .collect()
 }
 
-fn get_supported_extensions(phys_device: VkPhysicalDevice) -> Vec<VkExtensionProperties> {
+fn get_supported_extensions(
+    phys_device: VkPhysicalDevice,
+) -> Result<Vec<VkExtensionProperties>, Error> {
     unsafe {
         let mut count = 0;
         vkEnumerateDeviceExtensionProperties(phys_device, ptr::null(), &mut count, ptr::null_mut())
-            .check_err("get number of supported extensions");
+            .check_err("get number of supported extensions")?;
 
         let mut extensions = Vec::with_capacity(count as usize);
         extensions.resize(count as usize, VkExtensionProperties::default());
@@ -427,26 +681,27 @@ fn get_supported_extensions(phys_device: VkPhysicalDevice) -> Vec<VkExtensionPro
             &mut count,
             extensions.as_mut_ptr(),
         )
-        .check_err("get supported extensions");
+        .check_err("get supported extensions")?;
 
-        extensions
+        Ok(extensions)
     }
 }
 
 fn query_swapchain_support(
     phys_device: VkPhysicalDevice,
     surface: VkSurfaceKHR,
-) -> SwapchainSupport {
+) -> Result<SwapchainSupport, Error> {
     let mut details = SwapchainSupport::default();
 
     unsafe {
         vkGetPhysicalDeviceSurfaceCapabilitiesKHR(phys_device, surface, &mut details.capabilities)
-            .check_err("get physical device surface capabilities");
+            .check_err("get physical device surface capabilities")?;
     }
 
     details.formats = unsafe {
         let mut count = 0;
-        vkGetPhysicalDeviceSurfaceFormatsKHR(phys_device, surface, &mut count, ptr::null_mut());
+        vkGetPhysicalDeviceSurfaceFormatsKHR(phys_device, surface, &mut count, ptr::null_mut())
+            .check_err("get number of surface formats")?;
 
         let mut formats = Vec::new();
 
@@ -457,7 +712,8 @@ fn query_swapchain_support(
                 surface,
                 &mut count,
                 formats.as_mut_ptr(),
-            );
+            )
+            .check_err("get surface formats")?;
         }
 
         formats
@@ -470,7 +726,8 @@ fn query_swapchain_support(
             surface,
             &mut count,
             ptr::null_mut(),
-        );
+        )
+        .check_err("get number of present modes")?;
 
         let mut modes = Vec::new();
 
@@ -481,25 +738,32 @@ fn query_swapchain_support(
                 surface,
                 &mut count,
                 modes.as_mut_ptr(),
-            );
+            )
+            .check_err("get present modes")?;
         }
 
         modes
     };
 
-    details
+    Ok(details)
 }
 
 fn create_logical_device(
     phys_device: VkPhysicalDevice,
     queue_families: &QueueFamilies,
-) -> VkDevice {
+    layers: &[CString],
+) -> Result<VkDevice, Error> {
     let queue_priority = 1.0;
     let queue_create_infos = get_queue_create_infos(queue_families, &queue_priority);
 
     let enabled_features = VkPhysicalDeviceFeatures::default();
 
-    let required_extensions = get_required_extensions();
+    // Swapchain extension is only needed, and possibly only supported, when presenting
+    let required_extensions = if queue_families.present.is_some() {
+        get_required_extensions()
+    } else {
+        Vec::new()
+    };
     let req_exts_c_ptrs = convert_to_c_ptrs(&required_extensions);
 
     let mut create_info = VkDeviceCreateInfo {
@@ -512,21 +776,18 @@ fn create_logical_device(
         ..Default::default()
     };
 
-    let layers = get_validation_layers(false);
-    let c_ptrs = convert_to_c_ptrs(&layers);
+    let c_ptrs = convert_to_c_ptrs(layers);
 
-    if cfg!(debug_assertions) {
-        create_info.enabledLayerCount = c_ptrs.len().try_into().unwrap();
-        create_info.ppEnabledLayerNames = c_ptrs.as_ptr();
-    }
+    create_info.enabledLayerCount = c_ptrs.len().try_into().unwrap();
+    create_info.ppEnabledLayerNames = c_ptrs.as_ptr();
 
     let mut device = MaybeUninit::<VkDevice>::uninit();
 
     unsafe {
         vkCreateDevice(phys_device, &create_info, ptr::null(), device.as_mut_ptr())
-            .check_err("create logical device");
+            .check_err("create logical device")?;
 
-        device.assume_init()
+        Ok(device.assume_init())
     }
 }
 
@@ -537,7 +798,11 @@ fn get_queue_create_infos(
 ) -> Vec<VkDeviceQueueCreateInfo> {
     let mut queue_create_infos = Vec::new();
 
-    let mut unique_families = vec![families.graphics.unwrap(), families.present.unwrap()];
+    let mut unique_families = vec![families.graphics.unwrap()];
+
+    if let Some(present) = families.present {
+        unique_families.push(present);
+    }
 
     unique_families.sort_unstable();
     unique_families.dedup();
@@ -561,5 +826,5 @@ fn get_device_name(phys_device: VkPhysicalDevice) -> String {
     let properties = get_device_properties(phys_device);
     let cstr = unsafe { CStr::from_ptr(properties.deviceName.as_ptr()) };
 
-    cstr.to_str().expect("invalid device name").to_string()
+    cstr.to_string_lossy().into_owned()
 }
@@ -0,0 +1,104 @@
+use glfw_sys::*;
+
+use crate::{Error, ErrorKind};
+
+use std::fmt;
+
+impl Error {
+    pub(crate) fn new(action: &'static str, kind: ErrorKind) -> Self {
+        Self { action, kind }
+    }
+
+    pub fn action(&self) -> &'static str {
+        self.action
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to {}: {}", self.action, self.kind)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Lets callers that report errors as strings use `?` on Vulkan calls
+impl From<Error> for String {
+    fn from(error: Error) -> Self {
+        error.to_string()
+    }
+}
+
+impl ErrorKind {
+    pub fn from_result(result: VkResult) -> Self {
+        match result {
+            VK_NOT_READY => Self::NotReady,
+            VK_TIMEOUT => Self::Timeout,
+            VK_INCOMPLETE => Self::Incomplete,
+            VK_ERROR_OUT_OF_HOST_MEMORY => Self::OutOfHostMemory,
+            VK_ERROR_OUT_OF_DEVICE_MEMORY => Self::OutOfDeviceMemory,
+            VK_ERROR_INITIALIZATION_FAILED => Self::InitializationFailed,
+            VK_ERROR_DEVICE_LOST => Self::DeviceLost,
+            VK_ERROR_MEMORY_MAP_FAILED => Self::MemoryMapFailed,
+            VK_ERROR_LAYER_NOT_PRESENT => Self::LayerNotPresent,
+            VK_ERROR_EXTENSION_NOT_PRESENT => Self::ExtensionNotPresent,
+            VK_ERROR_FEATURE_NOT_PRESENT => Self::FeatureNotPresent,
+            VK_ERROR_INCOMPATIBLE_DRIVER => Self::IncompatibleDriver,
+            VK_ERROR_TOO_MANY_OBJECTS => Self::TooManyObjects,
+            VK_ERROR_FORMAT_NOT_SUPPORTED => Self::FormatNotSupported,
+            VK_ERROR_FRAGMENTED_POOL => Self::FragmentedPool,
+            VK_ERROR_OUT_OF_POOL_MEMORY => Self::OutOfPoolMemory,
+            VK_ERROR_INVALID_EXTERNAL_HANDLE => Self::InvalidExternalHandle,
+            VK_ERROR_FRAGMENTATION => Self::Fragmentation,
+            VK_ERROR_SURFACE_LOST_KHR => Self::SurfaceLost,
+            VK_ERROR_NATIVE_WINDOW_IN_USE_KHR => Self::NativeWindowInUse,
+            VK_ERROR_OUT_OF_DATE_KHR => Self::OutOfDate,
+            VK_ERROR_INCOMPATIBLE_DISPLAY_KHR => Self::IncompatibleDisplay,
+            VK_ERROR_VALIDATION_FAILED_EXT => Self::ValidationFailed,
+            VK_ERROR_INVALID_SHADER_NV => Self::InvalidShader,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            Self::NotReady => "not ready",
+            Self::Timeout => "timed out",
+            Self::Incomplete => "incomplete",
+            Self::OutOfHostMemory => "out of host memory",
+            Self::OutOfDeviceMemory => "out of device memory",
+            Self::InitializationFailed => "initialization failed",
+            Self::DeviceLost => "device lost",
+            Self::MemoryMapFailed => "memory map failed",
+            Self::LayerNotPresent => "layer not present",
+            Self::ExtensionNotPresent => "extension not present",
+            Self::FeatureNotPresent => "feature not present",
+            Self::IncompatibleDriver => "incompatible driver",
+            Self::TooManyObjects => "too many objects",
+            Self::FormatNotSupported => "format not supported",
+            Self::FragmentedPool => "fragmented pool",
+            Self::OutOfPoolMemory => "out of pool memory",
+            Self::InvalidExternalHandle => "invalid external handle",
+            Self::Fragmentation => "fragmentation",
+            Self::SurfaceLost => "surface lost",
+            Self::NativeWindowInUse => "native window in use",
+            Self::OutOfDate => "out of date",
+            Self::IncompatibleDisplay => "incompatible display",
+            Self::ValidationFailed => "validation failed",
+            Self::InvalidShader => "invalid shader",
+            Self::NoDevice => "no Vulkan-capable device found",
+            Self::NoSuitableDevice => "no suitable device found",
+            Self::DeviceNotFound => "no device matches the selection",
+            Self::NoSuitableMemoryType => "no suitable memory type",
+            Self::Other(code) => return write!(f, "error code {}", code),
+        };
+
+        f.write_str(description)
+    }
+}
@@ -0,0 +1,226 @@
+use glfw_sys::*;
+
+use crate::utils::{find_memory_type, CheckVkError};
+use crate::{Buffer, CommandPool, Device, Error, Image, ImageView, Queue};
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+impl Image {
+    /// Creates a device-local 2D image with optimal tiling
+    pub fn new(
+        device: &Device,
+        extent: VkExtent2D,
+        format: VkFormat,
+        usage: u32,
+    ) -> Result<Self, Error> {
+        Self::with_mip_levels(device, extent, format, usage, 1)
+    }
+
+    /// Creates an image through a staging buffer and leaves it in `final_layout`. With `mipmaps`
+    /// the full mip chain is generated from `data`, which requires the format to support linear
+    /// blits.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_data(
+        device: &Device,
+        command_pool: &CommandPool,
+        queue: &Queue,
+        extent: VkExtent2D,
+        format: VkFormat,
+        usage: u32,
+        final_layout: VkImageLayout,
+        data: &[u8],
+        mipmaps: bool,
+    ) -> Result<Self, Error> {
+        let mip_levels = if mipmaps {
+            32 - extent.width.max(extent.height).leading_zeros()
+        } else {
+            1
+        };
+
+        let mut staging_buffer = Buffer::new(
+            device,
+            data.len().try_into().unwrap(),
+            VK_BUFFER_USAGE_TRANSFER_SRC_BIT,
+            VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT,
+        )?;
+
+        staging_buffer.upload_to_buffer_memory(data)?;
+
+        let usage = usage | VK_IMAGE_USAGE_TRANSFER_SRC_BIT | VK_IMAGE_USAGE_TRANSFER_DST_BIT;
+        let image = Self::with_mip_levels(device, extent, format, usage, mip_levels)?;
+
+        let mut cmd_buffer = command_pool.create_command_buffer()?;
+
+        cmd_buffer.record_with_flags(VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT, |handle| {
+            handle.copy_buffer_to_image(&staging_buffer, &image, final_layout);
+        })?;
+
+        queue.submit(&cmd_buffer)?;
+
+        queue.wait_idle()?;
+
+        Ok(image)
+    }
+
+    fn with_mip_levels(
+        device: &Device,
+        extent: VkExtent2D,
+        format: VkFormat,
+        usage: u32,
+        mip_levels: u32,
+    ) -> Result<Self, Error> {
+        let create_info = VkImageCreateInfo {
+            sType: VK_STRUCTURE_TYPE_IMAGE_CREATE_INFO,
+            imageType: VK_IMAGE_TYPE_2D,
+            format,
+            extent: VkExtent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+            mipLevels: mip_levels,
+            arrayLayers: 1,
+            samples: VK_SAMPLE_COUNT_1_BIT,
+            tiling: VK_IMAGE_TILING_OPTIMAL,
+            usage,
+            sharingMode: VK_SHARING_MODE_EXCLUSIVE,
+            initialLayout: VK_IMAGE_LAYOUT_UNDEFINED,
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut image = MaybeUninit::<VkImage>::uninit();
+            vkCreateImage(device.as_raw(), &create_info, ptr::null(), image.as_mut_ptr())
+                .check_err("create image")?;
+            image.assume_init()
+        };
+
+        // Freeing null memory does nothing, so dropping this on later errors only frees the image
+        let mut created = Self {
+            raw,
+            memory: ptr::null_mut(),
+            format,
+            extent,
+            mip_levels,
+            device: device.as_raw(),
+        };
+
+        let mem_requirements = unsafe {
+            let mut requirements = MaybeUninit::<VkMemoryRequirements>::uninit();
+            vkGetImageMemoryRequirements(device.as_raw(), raw, requirements.as_mut_ptr());
+            requirements.assume_init()
+        };
+
+        let memory_type = find_memory_type(
+            device.phys_device,
+            mem_requirements.memoryTypeBits,
+            VK_MEMORY_PROPERTY_DEVICE_LOCAL_BIT,
+        )?;
+
+        let alloc_info = VkMemoryAllocateInfo {
+            sType: VK_STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO,
+            allocationSize: mem_requirements.size,
+            memoryTypeIndex: memory_type,
+            ..Default::default()
+        };
+
+        created.memory = unsafe {
+            let mut memory = MaybeUninit::<VkDeviceMemory>::uninit();
+
+            vkAllocateMemory(device.as_raw(), &alloc_info, ptr::null(), memory.as_mut_ptr())
+                .check_err("allocate image memory")?;
+
+            memory.assume_init()
+        };
+
+        unsafe {
+            vkBindImageMemory(device.as_raw(), raw, created.memory, 0)
+                .check_err("bind image memory")?;
+        }
+
+        Ok(created)
+    }
+
+    pub fn extent(&self) -> VkExtent2D {
+        self.extent
+    }
+
+    pub fn format(&self) -> VkFormat {
+        self.format
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.mip_levels
+    }
+
+    pub fn as_raw(&self) -> VkImage {
+        self.raw
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroyImage(self.device, self.raw, ptr::null());
+            vkFreeMemory(self.device, self.memory, ptr::null());
+        }
+    }
+}
+
+impl ImageView {
+    pub fn new(device: &Device, image: &Image) -> Result<Self, Error> {
+        Self::from_raw(device.as_raw(), image.raw, image.format, image.mip_levels)
+    }
+
+    pub(crate) fn from_raw(
+        device: VkDevice,
+        image: VkImage,
+        image_format: VkFormat,
+        mip_levels: u32,
+    ) -> Result<Self, Error> {
+        let create_info = VkImageViewCreateInfo {
+            sType: VK_STRUCTURE_TYPE_IMAGE_VIEW_CREATE_INFO,
+            image,
+            viewType: VK_IMAGE_VIEW_TYPE_2D,
+            format: image_format,
+            components: VkComponentMapping {
+                r: VK_COMPONENT_SWIZZLE_IDENTITY,
+                g: VK_COMPONENT_SWIZZLE_IDENTITY,
+                b: VK_COMPONENT_SWIZZLE_IDENTITY,
+                a: VK_COMPONENT_SWIZZLE_IDENTITY,
+            },
+            subresourceRange: VkImageSubresourceRange {
+                aspectMask: VK_IMAGE_ASPECT_COLOR_BIT,
+                baseMipLevel: 0,
+                levelCount: mip_levels,
+                baseArrayLayer: 0,
+                layerCount: 1,
+            },
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut view = MaybeUninit::<VkImageView>::uninit();
+
+            vkCreateImageView(device, &create_info, ptr::null(), view.as_mut_ptr())
+                .check_err("create image view")?;
+
+            view.assume_init()
+        };
+
+        Ok(Self { raw, device })
+    }
+
+    pub fn as_raw(&self) -> VkImageView {
+        self.raw
+    }
+}
+
+impl Drop for ImageView {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroyImageView(self.device, self.raw, ptr::null());
+        }
+    }
+}
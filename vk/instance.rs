@@ -1,7 +1,10 @@
 use glfw_sys::*;
 
+use crate::debug::{
+    create_messenger, destroy_messenger, messenger_create_info, supports_debug_utils,
+};
 use crate::utils::{convert_to_c_ptrs, get_validation_layers, CheckVkError};
-use crate::Instance;
+use crate::{Error, Instance, Validation};
 
 use std::ffi::{c_char, CStr, CString};
 use std::mem::MaybeUninit;
@@ -9,80 +12,106 @@ use std::ptr;
 use std::str::FromStr;
 
 impl Instance {
-    /// Create a new Vulkan Instance.
+    /// Create a new Vulkan Instance with a surface for `glfw_window`.
     ///
     /// # Panics
     ///
     /// Panics if `app_name` contains null byte in the middle.
-    pub fn new<S>(app_name: S, app_version: (u32, u32, u32), glfw_window: *mut GLFWwindow) -> Self
+    pub fn new<S>(
+        app_name: S,
+        app_version: (u32, u32, u32),
+        glfw_window: *mut GLFWwindow,
+        validation: &Validation,
+    ) -> Result<Self, Error>
     where
         S: Into<Vec<u8>>,
     {
-        let name_cstr = CString::new(app_name).unwrap();
-        let (app_major, app_minor, app_patch) = app_version;
-        let app_version_int = make_vk_version(app_major, app_minor, app_patch);
-
-        let ver_major = u32::from_str(env!("CARGO_PKG_VERSION_MAJOR")).unwrap();
-        let ver_minor = u32::from_str(env!("CARGO_PKG_VERSION_MINOR")).unwrap();
-        let ver_patch = u32::from_str(env!("CARGO_PKG_VERSION_PATCH")).unwrap();
-        let engine_version_int = make_vk_version(ver_major, ver_minor, ver_patch);
-
-        let api_version = make_vk_api_version(0, 1, 3, 0);
-
-        let app_info = VkApplicationInfo {
-            sType: VK_STRUCTURE_TYPE_APPLICATION_INFO,
-            pApplicationName: name_cstr.as_ptr(),
-            applicationVersion: app_version_int,
-            pEngineName: ptr::null(),
-            engineVersion: engine_version_int,
-            apiVersion: api_version,
-            pNext: ptr::null(),
-        };
-
         let mut extension_count = 0;
         let extension_names = unsafe { glfwGetRequiredInstanceExtensions(&mut extension_count) };
 
         print_required_extensions(extension_count, extension_names);
 
-        let mut create_info = VkInstanceCreateInfo {
-            sType: VK_STRUCTURE_TYPE_INSTANCE_CREATE_INFO,
-            pApplicationInfo: &app_info,
-            enabledExtensionCount: extension_count,
-            ppEnabledExtensionNames: extension_names,
-            ..Default::default()
+        let extensions = if extension_names.is_null() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(extension_names, extension_count as usize) }
         };
 
-        let layers = get_validation_layers(true);
-        let c_ptrs = convert_to_c_ptrs(&layers);
+        let mut instance = Self::create(app_name, app_version, extensions, validation)?;
 
-        if cfg!(debug_assertions) {
-            create_info.enabledLayerCount = c_ptrs.len().try_into().unwrap();
-            create_info.ppEnabledLayerNames = c_ptrs.as_ptr();
-        }
+        instance.glfw_window = glfw_window;
+        instance.surface = Some(create_surface(instance.raw, glfw_window)?);
+
+        Ok(instance)
+    }
 
-        let raw = unsafe {
-            let mut instance = MaybeUninit::<VkInstance>::uninit();
+    /// Create a new Vulkan Instance without a surface, for rendering offscreen. Does not require
+    /// GLFW or a display.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `app_name` contains null byte in the middle.
+    pub fn new_headless<S>(
+        app_name: S,
+        app_version: (u32, u32, u32),
+        validation: &Validation,
+    ) -> Result<Self, Error>
+    where
+        S: Into<Vec<u8>>,
+    {
+        Self::create(app_name, app_version, &[], validation)
+    }
 
-            vkCreateInstance(&create_info, ptr::null(), instance.as_mut_ptr())
-                .check_err("create instance");
+    /// `VK_EXT_debug_utils` is enabled whenever it's available, for object names and labels.
+    /// The debug messenger is only set up when some layers are enabled, as they are what reports
+    /// most messages.
+    fn create<S>(
+        app_name: S,
+        app_version: (u32, u32, u32),
+        window_extensions: &[*const c_char],
+        validation: &Validation,
+    ) -> Result<Self, Error>
+    where
+        S: Into<Vec<u8>>,
+    {
+        let layers = get_validation_layers(&validation.layers)?;
+        let validation = Box::new(validation.clone());
+        let mut extensions = window_extensions.to_vec();
 
-            instance.assume_init()
-        };
+        let debug_utils = supports_debug_utils(&layers)?;
 
-        let surface = create_surface(raw, glfw_window);
+        if debug_utils {
+            extensions.push(VK_EXT_DEBUG_UTILS_EXTENSION_NAME.as_ptr().cast());
+        } else if !layers.is_empty() {
+            eprintln!("VK_EXT_debug_utils is not supported, validation messages won't be shown");
+        }
 
-        Self {
+        let messenger_info =
+            (debug_utils && !layers.is_empty()).then(|| messenger_create_info(&validation));
+
+        let raw = create_instance(app_name, app_version, &extensions, &layers, messenger_info)?;
+
+        let mut instance = Self {
             raw,
-            surface,
-            glfw_window,
+            surface: None,
+            glfw_window: ptr::null_mut(),
+            layers,
+            messenger: None,
+            _validation: validation,
+        };
+
+        if let Some(messenger_info) = &messenger_info {
+            instance.messenger = Some(create_messenger(raw, messenger_info)?);
         }
+
+        Ok(instance)
     }
 
     pub fn as_raw(&self) -> VkInstance {
         self.raw
     }
 
-    pub fn surface(&self) -> VkSurfaceKHR {
+    pub fn surface(&self) -> Option<VkSurfaceKHR> {
         self.surface
     }
 }
@@ -90,12 +119,78 @@ impl Instance {
 impl Drop for Instance {
     fn drop(&mut self) {
         unsafe {
-            vkDestroySurfaceKHR(self.raw, self.surface, ptr::null());
+            if let Some(surface) = self.surface {
+                vkDestroySurfaceKHR(self.raw, surface, ptr::null());
+            }
+
+            if let Some(messenger) = self.messenger {
+                destroy_messenger(self.raw, messenger);
+            }
+
             vkDestroyInstance(self.raw, ptr::null());
         }
     }
 }
 
+fn create_instance<S>(
+    app_name: S,
+    app_version: (u32, u32, u32),
+    extension_names: &[*const c_char],
+    layers: &[CString],
+    messenger_info: Option<VkDebugUtilsMessengerCreateInfoEXT>,
+) -> Result<VkInstance, Error>
+where
+    S: Into<Vec<u8>>,
+{
+    let name_cstr = CString::new(app_name).unwrap();
+    let (app_major, app_minor, app_patch) = app_version;
+    let app_version_int = make_vk_version(app_major, app_minor, app_patch);
+
+    let ver_major = u32::from_str(env!("CARGO_PKG_VERSION_MAJOR")).unwrap();
+    let ver_minor = u32::from_str(env!("CARGO_PKG_VERSION_MINOR")).unwrap();
+    let ver_patch = u32::from_str(env!("CARGO_PKG_VERSION_PATCH")).unwrap();
+    let engine_version_int = make_vk_version(ver_major, ver_minor, ver_patch);
+
+    let api_version = make_vk_api_version(0, 1, 3, 0);
+
+    let app_info = VkApplicationInfo {
+        sType: VK_STRUCTURE_TYPE_APPLICATION_INFO,
+        pApplicationName: name_cstr.as_ptr(),
+        applicationVersion: app_version_int,
+        pEngineName: ptr::null(),
+        engineVersion: engine_version_int,
+        apiVersion: api_version,
+        pNext: ptr::null(),
+    };
+
+    let mut create_info = VkInstanceCreateInfo {
+        sType: VK_STRUCTURE_TYPE_INSTANCE_CREATE_INFO,
+        pApplicationInfo: &app_info,
+        enabledExtensionCount: extension_names.len().try_into().unwrap(),
+        ppEnabledExtensionNames: extension_names.as_ptr(),
+        ..Default::default()
+    };
+
+    // Also reports problems in creating and destroying the instance itself
+    if let Some(messenger_info) = &messenger_info {
+        create_info.pNext = ptr::from_ref(messenger_info).cast();
+    }
+
+    let c_ptrs = convert_to_c_ptrs(layers);
+
+    create_info.enabledLayerCount = c_ptrs.len().try_into().unwrap();
+    create_info.ppEnabledLayerNames = c_ptrs.as_ptr();
+
+    unsafe {
+        let mut instance = MaybeUninit::<VkInstance>::uninit();
+
+        vkCreateInstance(&create_info, ptr::null(), instance.as_mut_ptr())
+            .check_err("create instance")?;
+
+        Ok(instance.assume_init())
+    }
+}
+
 fn make_vk_version(major: u32, minor: u32, patch: u32) -> u32 {
     (major << 22) | (minor << 12) | patch
 }
@@ -105,7 +200,7 @@ fn make_vk_api_version(variant: u32, major: u32, minor: u32, patch: u32) -> u32
 }
 
 fn print_required_extensions(count: u32, names: *mut *const c_char) {
-    println!("Required extensions:");
+    eprintln!("Required extensions:");
 
     for i in 0..count {
         let cstr = unsafe {
@@ -113,16 +208,19 @@ fn print_required_extensions(count: u32, names: *mut *const c_char) {
             CStr::from_ptr(ptr)
         };
 
-        println!("\t{:?}", cstr);
+        eprintln!("\t{:?}", cstr);
     }
 }
 
-fn create_surface(instance: VkInstance, glfw_window: *mut GLFWwindow) -> VkSurfaceKHR {
+fn create_surface(
+    instance: VkInstance,
+    glfw_window: *mut GLFWwindow,
+) -> Result<VkSurfaceKHR, Error> {
     let mut surface = MaybeUninit::<VkSurfaceKHR>::uninit();
 
     unsafe {
         glfwCreateWindowSurface(instance, glfw_window, ptr::null(), surface.as_mut_ptr())
-            .check_err("create window surface");
-        surface.assume_init()
+            .check_err("create window surface")?;
+        Ok(surface.assume_init())
     }
 }
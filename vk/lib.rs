@@ -3,18 +3,27 @@
     clippy::wildcard_imports,
     clippy::must_use_candidate,
     clippy::uninlined_format_args,
-    clippy::missing_panics_doc
+    clippy::missing_panics_doc,
+    clippy::missing_errors_doc
 )]
 
 use glfw_sys::*;
 
+use std::ffi::CString;
+
 mod buffer;
 mod command;
+mod debug;
+mod descriptor;
 mod device;
+mod error;
+mod image;
 mod instance;
 mod pipeline;
+mod query;
 mod queue;
 mod render_pass;
+mod sampler;
 mod shader;
 mod swapchain;
 mod sync;
@@ -23,25 +32,46 @@ pub mod utils;
 
 pub struct Instance {
     raw: VkInstance,
-    surface: VkSurfaceKHR,
+    surface: Option<VkSurfaceKHR>,
     glfw_window: *mut GLFWwindow,
+    /// Enabled layers, which devices also get for compatibility with older implementations
+    layers: Vec<CString>,
+    messenger: Option<VkDebugUtilsMessengerEXT>,
+    /// Boxed so that the messenger callback can keep a pointer to it
+    _validation: Box<Validation>,
+}
+
+/// Layers and debug messages to enable on an `Instance`, none by default
+#[derive(Clone, Debug, Default)]
+pub struct Validation {
+    /// Names of the layers. Those that aren't installed are skipped with a warning.
+    pub layers: Vec<String>,
+    /// Log info and verbose messages too, not only warnings and errors
+    pub verbose: bool,
+    /// Make the call that a validation error was reported for fail with
+    /// `ErrorKind::ValidationFailed`
+    pub fail_on_error: bool,
 }
 
 pub struct Device {
     phys_device: VkPhysicalDevice,
     device: VkDevice,
     queue_families: QueueFamilies,
-    swapchain_support: SwapchainSupport,
+    labels: DebugLabels,
 }
 
 pub struct Queue {
     raw: VkQueue,
+    device: VkDevice,
 }
 
 pub struct Swapchain {
     raw: VkSwapchainKHR,
+    images: Vec<VkImage>,
     format: VkFormat,
     extent: VkExtent2D,
+    usage: u32,
+    present_mode: VkPresentModeKHR,
     device: VkDevice,
 }
 
@@ -66,19 +96,67 @@ pub struct Pipeline {
     device: VkDevice,
 }
 
+pub struct ComputePipeline {
+    raw: VkPipeline,
+    device: VkDevice,
+}
+
 pub struct Framebuffer {
     raw: VkFramebuffer,
     device: VkDevice,
 }
 
+pub struct Image {
+    raw: VkImage,
+    memory: VkDeviceMemory,
+    format: VkFormat,
+    extent: VkExtent2D,
+    mip_levels: u32,
+    device: VkDevice,
+}
+
 pub struct ImageView {
     raw: VkImageView,
     device: VkDevice,
 }
 
+pub struct Sampler {
+    raw: VkSampler,
+    device: VkDevice,
+}
+
+pub struct DescriptorSetLayout {
+    raw: VkDescriptorSetLayout,
+    device: VkDevice,
+}
+
+pub struct DescriptorPool {
+    raw: VkDescriptorPool,
+    device: VkDevice,
+}
+
+pub struct DescriptorSet {
+    raw: VkDescriptorSet,
+    device: VkDevice,
+}
+
+pub struct DescriptorBinding {
+    pub binding: u32,
+    pub descriptor_type: VkDescriptorType,
+    pub count: u32,
+    pub stages: u32,
+}
+
+pub struct QueryPool {
+    raw: VkQueryPool,
+    count: u32,
+    device: VkDevice,
+}
+
 pub struct CommandPool {
     raw: VkCommandPool,
     device: VkDevice,
+    labels: DebugLabels,
 }
 
 #[derive(Clone)]
@@ -86,10 +164,12 @@ pub struct CommandBuffer {
     raw: VkCommandBuffer,
     device: VkDevice,
     cmd_pool: VkCommandPool,
+    labels: DebugLabels,
 }
 
 pub struct CommandBufferRecording {
     cmd_buf: VkCommandBuffer,
+    labels: DebugLabels,
 }
 
 pub struct Semaphore {
@@ -108,6 +188,70 @@ pub struct Buffer {
     device: VkDevice,
 }
 
+/// Wrappers whose objects can be named, so that validation messages and capture tools show the
+/// name instead of a bare handle
+pub trait DebugName {
+    /// Does nothing when `VK_EXT_debug_utils` isn't enabled. Names are only a debugging aid, so
+    /// failures are printed rather than returned.
+    fn set_name(&self, name: &str);
+}
+
+/// Failure of a Vulkan call, or of a requirement that this crate checks itself
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// What was being done, like "create buffer"
+    action: &'static str,
+    kind: ErrorKind,
+}
+
+/// Named `VkResult` codes, plus the failures that don't come from Vulkan
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    NotReady,
+    Timeout,
+    Incomplete,
+    OutOfHostMemory,
+    OutOfDeviceMemory,
+    InitializationFailed,
+    DeviceLost,
+    MemoryMapFailed,
+    LayerNotPresent,
+    ExtensionNotPresent,
+    FeatureNotPresent,
+    IncompatibleDriver,
+    TooManyObjects,
+    FormatNotSupported,
+    FragmentedPool,
+    OutOfPoolMemory,
+    InvalidExternalHandle,
+    Fragmentation,
+    SurfaceLost,
+    NativeWindowInUse,
+    OutOfDate,
+    IncompatibleDisplay,
+    ValidationFailed,
+    InvalidShader,
+    /// No physical device was reported at all
+    NoDevice,
+    /// Devices exist, but none has the required queues and extensions
+    NoSuitableDevice,
+    /// No device matches a `DeviceSelector`
+    DeviceNotFound,
+    NoSuitableMemoryType,
+    /// Code without a variant of its own
+    Other(VkResult),
+}
+
+/// Physical device to use instead of the automatic choice, see `Device::with_selector`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeviceSelector {
+    /// Position in the order that devices are enumerated and printed in
+    Index(usize),
+    /// Part of the device name, ignoring case
+    Name(String),
+    Uuid([u8; 16]),
+}
+
 #[derive(Clone, Copy)]
 pub enum QueueFamily {
     Graphics,
@@ -122,11 +266,24 @@ pub enum QueueFamily {
 pub enum ShaderType {
     Vertex,
     Fragment,
+    Compute,
+}
+
+/// `VK_EXT_debug_utils` functions for command buffer labels, looked up once per device. They are
+/// `None` when the extension isn't enabled.
+#[derive(Clone, Copy)]
+struct DebugLabels {
+    begin: PFN_vkCmdBeginDebugUtilsLabelEXT,
+    end: PFN_vkCmdEndDebugUtilsLabelEXT,
+    insert: PFN_vkCmdInsertDebugUtilsLabelEXT,
 }
 
 #[derive(Default)]
 struct QueueFamilies {
+    /// Also supports compute
     graphics: Option<u32>,
+    /// Of the graphics family, 0 if it doesn't support timestamps
+    graphics_timestamp_bits: u32,
     compute: Option<u32>,
     transfer: Option<u32>,
     sparse_binding: Option<u32>,
@@ -1,22 +1,37 @@
 use glfw_sys::*;
 
-use crate::utils::{u32_to_f32_nowarn, CheckVkError};
-use crate::{Device, Pipeline, PipelineLayout, RenderPass, Shader, Swapchain};
+use crate::utils::CheckVkError;
+use crate::{
+    ComputePipeline, DescriptorSetLayout, Device, Error, Pipeline, PipelineLayout, RenderPass,
+    Shader,
+};
 
 use std::mem::{size_of, MaybeUninit};
 use std::ptr;
 
 impl PipelineLayout {
-    pub fn new<PushConstT>(device: &Device, push_const_stages: u32) -> Self {
+    pub fn new<PushConstT>(
+        device: &Device,
+        push_const_stages: u32,
+        set_layouts: &[&DescriptorSetLayout],
+    ) -> Result<Self, Error> {
+        let raw_set_layouts: Vec<VkDescriptorSetLayout> =
+            set_layouts.iter().map(|layout| layout.as_raw()).collect();
+
         let push_constant_range = VkPushConstantRange {
             stageFlags: push_const_stages,
             offset: 0,
             size: size_of::<PushConstT>().try_into().unwrap(),
         };
 
+        // Zero-sized types like () mean no push constants
+        let push_constant_range_count = u32::from(size_of::<PushConstT>() > 0);
+
         let create_info = VkPipelineLayoutCreateInfo {
             sType: VK_STRUCTURE_TYPE_PIPELINE_LAYOUT_CREATE_INFO,
-            pushConstantRangeCount: 1,
+            setLayoutCount: raw_set_layouts.len().try_into().unwrap(),
+            pSetLayouts: raw_set_layouts.as_ptr(),
+            pushConstantRangeCount: push_constant_range_count,
             pPushConstantRanges: &push_constant_range,
             ..Default::default()
         };
@@ -30,15 +45,15 @@ impl PipelineLayout {
                 ptr::null_mut(),
                 layout.as_mut_ptr(),
             )
-            .check_err("create pipeline layout");
+            .check_err("create pipeline layout")?;
 
             layout.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+        })
     }
 
     fn as_raw(&self) -> VkPipelineLayout {
@@ -58,10 +73,9 @@ impl Pipeline {
     pub fn new(
         device: &Device,
         shaders: &[Shader],
-        swapchain: &Swapchain,
         render_pass: &RenderPass,
         pipeline_layout: &PipelineLayout,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         let shader_stage_infos: Vec<VkPipelineShaderStageCreateInfo> =
             shaders.iter().map(Shader::stage_info).collect();
 
@@ -72,9 +86,10 @@ impl Pipeline {
 
         let input_assembly = create_pipeline_input_assembly();
 
-        let viewport = create_pipeline_viewport(swapchain.extent);
-        let scissor = create_pipeline_scissor(swapchain.extent);
-        let viewport_state = create_static_viewport_state_info(&viewport, &scissor);
+        // Set when recording, so that the pipeline doesn't depend on the size of the window
+        let viewport_state = create_dynamic_viewport_state_info();
+        let dynamic_states = [VK_DYNAMIC_STATE_VIEWPORT, VK_DYNAMIC_STATE_SCISSOR];
+        let dynamic_state = create_dynamic_state_info(&dynamic_states);
 
         let rasterizer = create_rasterizer_info();
 
@@ -93,6 +108,7 @@ impl Pipeline {
             pRasterizationState: &rasterizer,
             pMultisampleState: &multisampling,
             pColorBlendState: &blending,
+            pDynamicState: &dynamic_state,
             layout: pipeline_layout.as_raw(),
             renderPass: render_pass.as_raw(),
             subpass: 0,
@@ -110,15 +126,15 @@ impl Pipeline {
                 ptr::null_mut(),
                 pipeline.as_mut_ptr(),
             )
-            .check_err("create pipeline");
+            .check_err("create pipeline")?;
 
             pipeline.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+        })
     }
 }
 
@@ -130,6 +146,50 @@ impl Drop for Pipeline {
     }
 }
 
+impl ComputePipeline {
+    pub fn new(
+        device: &Device,
+        shader: &Shader,
+        pipeline_layout: &PipelineLayout,
+    ) -> Result<Self, Error> {
+        let create_info = VkComputePipelineCreateInfo {
+            sType: VK_STRUCTURE_TYPE_COMPUTE_PIPELINE_CREATE_INFO,
+            stage: shader.stage_info(),
+            layout: pipeline_layout.as_raw(),
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut pipeline = MaybeUninit::<VkPipeline>::uninit();
+
+            vkCreateComputePipelines(
+                device.as_raw(),
+                ptr::null_mut(),
+                1,
+                &create_info,
+                ptr::null_mut(),
+                pipeline.as_mut_ptr(),
+            )
+            .check_err("create compute pipeline")?;
+
+            pipeline.assume_init()
+        };
+
+        Ok(Self {
+            raw,
+            device: device.as_raw(),
+        })
+    }
+}
+
+impl Drop for ComputePipeline {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroyPipeline(self.device, self.raw, ptr::null());
+        }
+    }
+}
+
 fn get_binding_description() -> VkVertexInputBindingDescription {
     let vec2_stride = 2 * size_of::<f32>();
 
@@ -172,34 +232,24 @@ fn create_pipeline_input_assembly() -> VkPipelineInputAssemblyStateCreateInfo {
     }
 }
 
-fn create_pipeline_viewport(extent: VkExtent2D) -> VkViewport {
-    VkViewport {
-        x: 0.0,
-        y: 0.0,
-        width: u32_to_f32_nowarn(extent.width),
-        height: u32_to_f32_nowarn(extent.height),
-        minDepth: 0.0,
-        maxDepth: 1.0,
-    }
-}
-
-fn create_pipeline_scissor(extent: VkExtent2D) -> VkRect2D {
-    VkRect2D {
-        offset: VkOffset2D { x: 0, y: 0 },
-        extent,
-    }
-}
-
-fn create_static_viewport_state_info(
-    viewport: &VkViewport,
-    scissor: &VkRect2D,
-) -> VkPipelineViewportStateCreateInfo {
+fn create_dynamic_viewport_state_info() -> VkPipelineViewportStateCreateInfo {
     VkPipelineViewportStateCreateInfo {
         sType: VK_STRUCTURE_TYPE_PIPELINE_VIEWPORT_STATE_CREATE_INFO,
         viewportCount: 1,
-        pViewports: viewport as *const VkViewport,
+        pViewports: ptr::null(),
         scissorCount: 1,
-        pScissors: scissor as *const VkRect2D,
+        pScissors: ptr::null(),
+        ..Default::default()
+    }
+}
+
+fn create_dynamic_state_info(
+    dynamic_states: &[VkDynamicState],
+) -> VkPipelineDynamicStateCreateInfo {
+    VkPipelineDynamicStateCreateInfo {
+        sType: VK_STRUCTURE_TYPE_PIPELINE_DYNAMIC_STATE_CREATE_INFO,
+        dynamicStateCount: dynamic_states.len().try_into().unwrap(),
+        pDynamicStates: dynamic_states.as_ptr(),
         ..Default::default()
     }
 }
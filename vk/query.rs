@@ -0,0 +1,78 @@
+use glfw_sys::*;
+
+use crate::utils::CheckVkError;
+use crate::{Device, Error, QueryPool};
+
+use std::ffi::c_void;
+use std::mem::{size_of, MaybeUninit};
+use std::ptr;
+
+impl QueryPool {
+    /// Pool of `count` timestamp queries. They must be reset before they are written.
+    pub fn new_timestamps(device: &Device, count: u32) -> Result<Self, Error> {
+        let create_info = VkQueryPoolCreateInfo {
+            sType: VK_STRUCTURE_TYPE_QUERY_POOL_CREATE_INFO,
+            queryType: VK_QUERY_TYPE_TIMESTAMP,
+            queryCount: count,
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut pool = MaybeUninit::<VkQueryPool>::uninit();
+
+            vkCreateQueryPool(device.as_raw(), &create_info, ptr::null(), pool.as_mut_ptr())
+                .check_err("create query pool")?;
+
+            pool.assume_init()
+        };
+
+        Ok(Self {
+            raw,
+            count,
+            device: device.as_raw(),
+        })
+    }
+
+    /// Returns the raw values of all queries, or `None` if some of them are not available yet.
+    /// Doesn't wait for the GPU.
+    pub fn get_results(&self) -> Result<Option<Vec<u64>>, Error> {
+        let mut results = vec![0u64; self.count as usize];
+
+        let result = unsafe {
+            vkGetQueryPoolResults(
+                self.device,
+                self.raw,
+                0,
+                self.count,
+                results.len() * size_of::<u64>(),
+                results.as_mut_ptr().cast::<c_void>(),
+                size_of::<u64>() as u64,
+                VK_QUERY_RESULT_64_BIT,
+            )
+        };
+
+        if result == VK_NOT_READY {
+            return Ok(None);
+        }
+
+        result.check_err("get query pool results")?;
+
+        Ok(Some(results))
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn as_raw(&self) -> VkQueryPool {
+        self.raw
+    }
+}
+
+impl Drop for QueryPool {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroyQueryPool(self.device, self.raw, ptr::null());
+        }
+    }
+}
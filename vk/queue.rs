@@ -1,7 +1,7 @@
 use glfw_sys::*;
 
 use crate::utils::CheckVkError;
-use crate::{CommandBuffer, Device, Fence, Queue, Semaphore, Swapchain};
+use crate::{CommandBuffer, Device, Error, Fence, Queue, Semaphore, Swapchain};
 
 use std::mem::MaybeUninit;
 use std::ptr;
@@ -14,7 +14,10 @@ impl Queue {
             queue.assume_init()
         };
 
-        Self { raw }
+        Self {
+            raw,
+            device: device.as_raw(),
+        }
     }
 
     pub fn submit_wait(
@@ -24,7 +27,7 @@ impl Queue {
         wait_semaphore: &Semaphore,
         signal_semaphore: &Semaphore,
         fence: &Fence,
-    ) {
+    ) -> Result<(), Error> {
         let submit_info = VkSubmitInfo {
             sType: VK_STRUCTURE_TYPE_SUBMIT_INFO,
             waitSemaphoreCount: 1,
@@ -38,11 +41,11 @@ impl Queue {
         };
 
         unsafe {
-            vkQueueSubmit(self.raw, 1, &submit_info, fence.as_raw()).check_err("submit to queue");
+            vkQueueSubmit(self.raw, 1, &submit_info, fence.as_raw()).check_err("submit to queue")
         }
     }
 
-    pub fn submit(&self, cmd_buf: &CommandBuffer) {
+    pub fn submit(&self, cmd_buf: &CommandBuffer) -> Result<(), Error> {
         let submit_info = VkSubmitInfo {
             sType: VK_STRUCTURE_TYPE_SUBMIT_INFO,
             commandBufferCount: 1,
@@ -51,11 +54,18 @@ impl Queue {
         };
 
         unsafe {
-            vkQueueSubmit(self.raw, 1, &submit_info, ptr::null_mut()).check_err("submit to queue");
+            vkQueueSubmit(self.raw, 1, &submit_info, ptr::null_mut()).check_err("submit to queue")
         }
     }
 
-    pub fn present(&self, wait_semaphore: &Semaphore, swapchain: &Swapchain, image_idx: u32) {
+    /// Returns whether need to recreate swapchain, either because it's out of date or no longer
+    /// matches the surface exactly
+    pub fn present(
+        &self,
+        wait_semaphore: &Semaphore,
+        swapchain: &Swapchain,
+        image_idx: u32,
+    ) -> Result<bool, Error> {
         let present_info = VkPresentInfoKHR {
             sType: VK_STRUCTURE_TYPE_PRESENT_INFO_KHR,
             waitSemaphoreCount: 1,
@@ -66,14 +76,18 @@ impl Queue {
             ..Default::default()
         };
 
-        unsafe {
-            vkQueuePresentKHR(self.raw, &present_info);
+        let result = unsafe { vkQueuePresentKHR(self.raw, &present_info) };
+
+        if result == VK_ERROR_OUT_OF_DATE_KHR || result == VK_SUBOPTIMAL_KHR {
+            return Ok(true);
         }
+
+        result.check_err("present")?;
+
+        Ok(false)
     }
 
-    pub fn wait_idle(&self) {
-        unsafe {
-            vkQueueWaitIdle(self.raw);
-        }
+    pub fn wait_idle(&self) -> Result<(), Error> {
+        unsafe { vkQueueWaitIdle(self.raw).check_err("wait for queue to become idle") }
     }
 }
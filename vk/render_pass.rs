@@ -1,13 +1,13 @@
 use glfw_sys::*;
 
 use crate::utils::CheckVkError;
-use crate::{Device, RenderPass};
+use crate::{Device, Error, RenderPass};
 
 use std::mem::MaybeUninit;
 use std::ptr;
 
 impl RenderPass {
-    pub fn new(device: &Device, image_format: u32) -> Self {
+    pub fn new(device: &Device, image_format: u32, final_layout: u32) -> Result<Self, Error> {
         let color_attachment = VkAttachmentDescription {
             format: image_format,
             samples: VK_SAMPLE_COUNT_1_BIT,
@@ -16,7 +16,7 @@ impl RenderPass {
             stencilLoadOp: VK_ATTACHMENT_LOAD_OP_DONT_CARE,
             stencilStoreOp: VK_ATTACHMENT_STORE_OP_DONT_CARE,
             initialLayout: VK_IMAGE_LAYOUT_UNDEFINED,
-            finalLayout: VK_IMAGE_LAYOUT_PRESENT_SRC_KHR,
+            finalLayout: final_layout,
             ..Default::default()
         };
 
@@ -62,15 +62,15 @@ impl RenderPass {
                 ptr::null_mut(),
                 render_pass.as_mut_ptr(),
             )
-            .check_err("create render pass");
+            .check_err("create render pass")?;
 
             render_pass.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+        })
     }
 
     pub fn as_raw(&self) -> VkRenderPass {
@@ -0,0 +1,64 @@
+use glfw_sys::*;
+
+use crate::utils::CheckVkError;
+use crate::{Device, Error, Sampler};
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+impl Sampler {
+    /// `filter` is used for both magnification and minification, `address_mode` for all axes.
+    /// Every mip level of the sampled image is accessible.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(
+        device: &Device,
+        filter: u32,
+        mipmap_mode: u32,
+        address_mode: u32,
+    ) -> Result<Self, Error> {
+        let create_info = VkSamplerCreateInfo {
+            sType: VK_STRUCTURE_TYPE_SAMPLER_CREATE_INFO,
+            magFilter: filter,
+            minFilter: filter,
+            mipmapMode: mipmap_mode,
+            addressModeU: address_mode,
+            addressModeV: address_mode,
+            addressModeW: address_mode,
+            mipLodBias: 0.0,
+            anisotropyEnable: 0,
+            maxAnisotropy: 1.0,
+            compareEnable: 0,
+            minLod: 0.0,
+            maxLod: VK_LOD_CLAMP_NONE as f32,
+            borderColor: VK_BORDER_COLOR_FLOAT_TRANSPARENT_BLACK,
+            unnormalizedCoordinates: 0,
+            ..Default::default()
+        };
+
+        let raw = unsafe {
+            let mut sampler = MaybeUninit::<VkSampler>::uninit();
+
+            vkCreateSampler(device.as_raw(), &create_info, ptr::null(), sampler.as_mut_ptr())
+                .check_err("create sampler")?;
+
+            sampler.assume_init()
+        };
+
+        Ok(Self {
+            raw,
+            device: device.as_raw(),
+        })
+    }
+
+    pub fn as_raw(&self) -> VkSampler {
+        self.raw
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        unsafe {
+            vkDestroySampler(self.device, self.raw, ptr::null());
+        }
+    }
+}
@@ -1,23 +1,27 @@
 use glfw_sys::*;
 
 use crate::utils::CheckVkError;
-use crate::{Device, Shader, ShaderType};
+use crate::{Device, Error, ErrorKind, Shader, ShaderType};
 
 use std::ffi::CStr;
 use std::mem::MaybeUninit;
 use std::ptr;
 
 impl Shader {
-    pub fn from_bytes(device: &Device, compiled: &[u8], sh_type: ShaderType) -> Self {
-        let module = create_shader_module(device.as_raw(), compiled);
+    pub fn from_bytes(
+        device: &Device,
+        compiled: &[u8],
+        sh_type: ShaderType,
+    ) -> Result<Self, Error> {
+        let module = create_shader_module(device.as_raw(), compiled)?;
         let entrypoint = CStr::from_bytes_with_nul(b"main\0").unwrap();
         let stage_info = create_shader_stage_info(module, sh_type, entrypoint);
 
-        Self {
+        Ok(Self {
             module,
             stage_info,
             device: device.as_raw(),
-        }
+        })
     }
 
     pub fn stage_info(&self) -> VkPipelineShaderStageCreateInfo {
@@ -33,7 +37,12 @@ impl Drop for Shader {
     }
 }
 
-fn create_shader_module(device: VkDevice, bytes: &[u8]) -> VkShaderModule {
+fn create_shader_module(device: VkDevice, bytes: &[u8]) -> Result<VkShaderModule, Error> {
+    // SPIR-V is made of 32-bit words
+    if bytes.len() % 4 != 0 {
+        return Err(Error::new("create shader module", ErrorKind::InvalidShader));
+    }
+
     let transmuted_copy = pack_to_u32s(bytes);
 
     let create_info = VkShaderModuleCreateInfo {
@@ -47,15 +56,13 @@ fn create_shader_module(device: VkDevice, bytes: &[u8]) -> VkShaderModule {
         let mut shader_module = MaybeUninit::<VkShaderModule>::uninit();
 
         vkCreateShaderModule(device, &create_info, ptr::null_mut(), shader_module.as_mut_ptr())
-            .check_err("create shader module");
+            .check_err("create shader module")?;
 
-        shader_module.assume_init()
+        Ok(shader_module.assume_init())
     }
 }
 
 fn pack_to_u32s(bytes: &[u8]) -> Vec<u32> {
-    assert!(bytes.len() % 4 == 0, "code length must be a multiple of 4");
-
     bytes
         .chunks_exact(4)
         .map(|chunk| match chunk {
@@ -73,6 +80,7 @@ fn create_shader_stage_info(
     let stage = match &sh_type {
         ShaderType::Vertex => VK_SHADER_STAGE_VERTEX_BIT,
         ShaderType::Fragment => VK_SHADER_STAGE_FRAGMENT_BIT,
+        ShaderType::Compute => VK_SHADER_STAGE_COMPUTE_BIT,
     };
 
     VkPipelineShaderStageCreateInfo {
@@ -1,21 +1,31 @@
 use glfw_sys::*;
 
-use crate::utils::CheckVkError;
-use crate::{Device, Framebuffer, ImageView, Instance, RenderPass, Semaphore, Swapchain};
+use crate::utils::{present_mode_name, CheckVkError};
+use crate::{Device, Error, Framebuffer, ImageView, Instance, RenderPass, Semaphore, Swapchain};
 
 use std::mem::MaybeUninit;
 use std::ptr;
 
 impl Swapchain {
-    pub fn from_device(device: &Device, instance: &Instance, verbose: bool) -> Self {
-        let surface_format = choose_swapchain_surface_format(&device.swapchain_support.formats);
+    /// Uses the first of `present_modes` that is supported, or FIFO if none are. `old_swapchain`
+    /// is retired, its images that were already acquired can still be presented.
+    pub fn from_device(
+        device: &Device,
+        instance: &Instance,
+        present_modes: &[VkPresentModeKHR],
+        old_swapchain: Option<&Swapchain>,
+        verbose: bool,
+    ) -> Result<Self, Error> {
+        let support = device.swapchain_support(instance)?;
+        let capabilities = support.capabilities;
+
+        let surface_format = choose_swapchain_surface_format(&support.formats);
         let present_mode =
-            choose_swapchain_present_mode(&device.swapchain_support.present_modes, verbose);
-        let extent =
-            choose_swapchain_extent(instance.glfw_window, device.swapchain_support.capabilities);
+            choose_swapchain_present_mode(&support.present_modes, present_modes, verbose);
+        let extent = choose_swapchain_extent(instance.glfw_window, capabilities);
 
-        let max_image_count = device.swapchain_support.capabilities.maxImageCount;
-        let mut image_count = device.swapchain_support.capabilities.minImageCount + 1;
+        let max_image_count = capabilities.maxImageCount;
+        let mut image_count = capabilities.minImageCount + 1;
 
         if image_count > max_image_count && max_image_count != 0 {
             image_count = max_image_count;
@@ -31,23 +41,28 @@ impl Swapchain {
             (VK_SHARING_MODE_CONCURRENT, 2, indices.as_ptr())
         };
 
+        // Transfer source allows copying presented frames, e.g. for screenshots
+        let supported_usage = capabilities.supportedUsageFlags;
+        let image_usage = VK_IMAGE_USAGE_COLOR_ATTACHMENT_BIT
+            | (supported_usage & VK_IMAGE_USAGE_TRANSFER_SRC_BIT);
+
         let create_info = VkSwapchainCreateInfoKHR {
             sType: VK_STRUCTURE_TYPE_SWAPCHAIN_CREATE_INFO_KHR,
-            surface: instance.surface,
+            surface: instance.surface().expect("swapchain requires a surface"),
             minImageCount: image_count,
             imageFormat: surface_format.format,
             imageColorSpace: surface_format.colorSpace,
             imageExtent: extent,
             imageArrayLayers: 1,
-            imageUsage: VK_IMAGE_USAGE_COLOR_ATTACHMENT_BIT,
+            imageUsage: image_usage,
             imageSharingMode: sharing_mode,
             queueFamilyIndexCount: qf_idx_count,
             pQueueFamilyIndices: qf_indices,
-            preTransform: device.swapchain_support.capabilities.currentTransform,
+            preTransform: capabilities.currentTransform,
             compositeAlpha: VK_COMPOSITE_ALPHA_OPAQUE_BIT_KHR,
             presentMode: present_mode,
             clipped: 1,
-            oldSwapchain: ptr::null_mut(),
+            oldSwapchain: old_swapchain.map_or(ptr::null_mut(), Swapchain::as_raw),
             ..Default::default()
         };
 
@@ -60,45 +75,51 @@ impl Swapchain {
                 ptr::null(),
                 swapchain.as_mut_ptr(),
             )
-            .check_err("create swapchain");
+            .check_err("create swapchain")?;
 
             swapchain.assume_init()
         };
 
-        Self {
+        let mut swapchain = Self {
             raw,
+            images: Vec::new(),
             format: surface_format.format,
             extent,
+            usage: image_usage,
+            present_mode,
             device: device.as_raw(),
-        }
-    }
+        };
 
-    pub fn get_image_views(&self) -> Vec<ImageView> {
-        let images = unsafe {
+        swapchain.images = unsafe {
             let mut count = 0;
-            vkGetSwapchainImagesKHR(self.device, self.raw, &mut count, ptr::null_mut());
+            vkGetSwapchainImagesKHR(device.as_raw(), raw, &mut count, ptr::null_mut())
+                .check_err("get number of swapchain images")?;
 
             let mut images = Vec::with_capacity(count as usize);
             images.resize(count as usize, ptr::null_mut());
 
-            vkGetSwapchainImagesKHR(self.device, self.raw, &mut count, images.as_mut_ptr());
+            vkGetSwapchainImagesKHR(device.as_raw(), raw, &mut count, images.as_mut_ptr())
+                .check_err("get swapchain images")?;
 
             images
         };
 
-        let mut image_views = Vec::with_capacity(images.len());
-
-        for image in images {
-            let image_view = ImageView::from_raw(self.device, image, self.format);
-
-            image_views.push(image_view);
-        }
+        Ok(swapchain)
+    }
 
-        image_views
+    pub fn get_image_views(&self) -> Result<Vec<ImageView>, Error> {
+        self.images
+            .iter()
+            .map(|&image| ImageView::from_raw(self.device, image, self.format, 1))
+            .collect()
     }
 
     /// Returns whether need to recreate swapchain
-    pub fn acquire_next_image(&self, semaphore: &mut Semaphore, image_index: &mut u32) -> bool {
+    pub fn acquire_next_image(
+        &self,
+        semaphore: &mut Semaphore,
+        image_index: &mut u32,
+    ) -> Result<bool, Error> {
         let result = unsafe {
             vkAcquireNextImageKHR(
                 self.device,
@@ -111,26 +132,35 @@ impl Swapchain {
         };
 
         if result == VK_ERROR_OUT_OF_DATE_KHR {
-            return true;
+            return Ok(true);
         }
 
         if result == VK_SUBOPTIMAL_KHR {
-            return false;
+            return Ok(false);
         }
 
-        result.check_err("acquire next image");
+        result.check_err("acquire next image")?;
 
-        false
+        Ok(false)
     }
 
     pub fn extent(&self) -> VkExtent2D {
         self.extent
     }
 
+    pub fn present_mode(&self) -> VkPresentModeKHR {
+        self.present_mode
+    }
+
     pub fn format(&self) -> VkFormat {
         self.format
     }
 
+    /// Whether images can be copied with `copy_swapchain_image_to_buffer`
+    pub fn supports_capture(&self) -> bool {
+        self.usage & VK_IMAGE_USAGE_TRANSFER_SRC_BIT != 0
+    }
+
     pub fn as_raw(&self) -> VkSwapchainKHR {
         self.raw
     }
@@ -149,15 +179,15 @@ impl Framebuffer {
         device: &Device,
         render_pass: &RenderPass,
         image_view: &ImageView,
-        swapchain: &Swapchain,
-    ) -> Self {
+        extent: VkExtent2D,
+    ) -> Result<Self, Error> {
         let create_info = VkFramebufferCreateInfo {
             sType: VK_STRUCTURE_TYPE_FRAMEBUFFER_CREATE_INFO,
             renderPass: render_pass.as_raw(),
             attachmentCount: 1,
             pAttachments: &image_view.as_raw(),
-            width: swapchain.extent.width,
-            height: swapchain.extent.height,
+            width: extent.width,
+            height: extent.height,
             layers: 1,
             ..Default::default()
         };
@@ -166,15 +196,15 @@ impl Framebuffer {
             let mut fb = MaybeUninit::<VkFramebuffer>::uninit();
 
             vkCreateFramebuffer(device.as_raw(), &create_info, ptr::null_mut(), fb.as_mut_ptr())
-                .check_err("create framebuffer");
+                .check_err("create framebuffer")?;
 
             fb.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+        })
     }
 
     pub fn as_raw(&self) -> VkFramebuffer {
@@ -190,54 +220,6 @@ impl Drop for Framebuffer {
     }
 }
 
-impl ImageView {
-    fn from_raw(device: VkDevice, image: VkImage, image_format: VkFormat) -> Self {
-        let create_info = VkImageViewCreateInfo {
-            sType: VK_STRUCTURE_TYPE_IMAGE_VIEW_CREATE_INFO,
-            image,
-            viewType: VK_IMAGE_VIEW_TYPE_2D,
-            format: image_format,
-            components: VkComponentMapping {
-                r: VK_COMPONENT_SWIZZLE_IDENTITY,
-                g: VK_COMPONENT_SWIZZLE_IDENTITY,
-                b: VK_COMPONENT_SWIZZLE_IDENTITY,
-                a: VK_COMPONENT_SWIZZLE_IDENTITY,
-            },
-            subresourceRange: VkImageSubresourceRange {
-                aspectMask: VK_IMAGE_ASPECT_COLOR_BIT,
-                baseMipLevel: 0,
-                levelCount: 1,
-                baseArrayLayer: 0,
-                layerCount: 1,
-            },
-            ..Default::default()
-        };
-
-        let raw = unsafe {
-            let mut view = MaybeUninit::<VkImageView>::uninit();
-
-            vkCreateImageView(device, &create_info, ptr::null(), view.as_mut_ptr())
-                .check_err("create image view");
-
-            view.assume_init()
-        };
-
-        Self { raw, device }
-    }
-
-    pub fn as_raw(&self) -> VkImageView {
-        self.raw
-    }
-}
-
-impl Drop for ImageView {
-    fn drop(&mut self) {
-        unsafe {
-            vkDestroyImageView(self.device, self.raw, ptr::null());
-        }
-    }
-}
-
 fn choose_swapchain_surface_format(formats: &[VkSurfaceFormatKHR]) -> VkSurfaceFormatKHR {
     for format in formats {
         if format.format == VK_FORMAT_B8G8R8_SRGB
@@ -252,43 +234,26 @@ fn choose_swapchain_surface_format(formats: &[VkSurfaceFormatKHR]) -> VkSurfaceF
 
 fn choose_swapchain_present_mode(
     present_modes: &[VkPresentModeKHR],
+    mode_priorities: &[VkPresentModeKHR],
     verbose: bool,
 ) -> VkPresentModeKHR {
     if verbose {
         print_present_modes(present_modes);
     }
 
-    let mode_priorities = [
-        VK_PRESENT_MODE_IMMEDIATE_KHR,
-        VK_PRESENT_MODE_FIFO_RELAXED_KHR,
-        VK_PRESENT_MODE_MAILBOX_KHR,
-        VK_PRESENT_MODE_FIFO_KHR,
-    ];
-
-    for mode in mode_priorities {
-        if present_modes.iter().any(|m| *m == mode) {
-            return mode;
-        }
-    }
-
-    VK_PRESENT_MODE_FIFO_KHR
+    // FIFO is always supported
+    mode_priorities
+        .iter()
+        .copied()
+        .find(|mode| present_modes.contains(mode))
+        .unwrap_or(VK_PRESENT_MODE_FIFO_KHR)
 }
 
 fn print_present_modes(present_modes: &[VkPresentModeKHR]) {
-    println!("Present modes:");
+    eprintln!("Present modes:");
 
     for mode in present_modes {
-        let desc = match *mode {
-            VK_PRESENT_MODE_IMMEDIATE_KHR => "Immediate",
-            VK_PRESENT_MODE_MAILBOX_KHR => "Mailbox",
-            VK_PRESENT_MODE_FIFO_KHR => "FIFO",
-            VK_PRESENT_MODE_FIFO_RELAXED_KHR => "FIFO relaxed",
-            VK_PRESENT_MODE_SHARED_DEMAND_REFRESH_KHR => "Shared on-demand refresh",
-            VK_PRESENT_MODE_SHARED_CONTINUOUS_REFRESH_KHR => "Shared continuous refresh",
-            _ => "Unknown",
-        };
-
-        println!("\t{}", desc);
+        eprintln!("\t{}", present_mode_name(*mode));
     }
 }
 
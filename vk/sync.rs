@@ -1,13 +1,13 @@
 use glfw_sys::*;
 
 use crate::utils::CheckVkError;
-use crate::{Device, Fence, Semaphore};
+use crate::{Device, Error, Fence, Semaphore};
 
 use std::mem::MaybeUninit;
 use std::ptr;
 
 impl Semaphore {
-    pub fn new(device: &Device) -> Self {
+    pub fn new(device: &Device) -> Result<Self, Error> {
         let create_info = VkSemaphoreCreateInfo {
             sType: VK_STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
             ..Default::default()
@@ -22,15 +22,15 @@ impl Semaphore {
                 ptr::null_mut(),
                 semaphore.as_mut_ptr(),
             )
-            .check_err("create semaphore");
+            .check_err("create semaphore")?;
 
             semaphore.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+        })
     }
 
     pub fn as_raw(&self) -> VkSemaphore {
@@ -47,7 +47,7 @@ impl Drop for Semaphore {
 }
 
 impl Fence {
-    pub fn new(device: &Device, signaled: bool) -> Self {
+    pub fn new(device: &Device, signaled: bool) -> Result<Self, Error> {
         let flags = if signaled { VK_FENCE_CREATE_SIGNALED_BIT } else { 0 };
 
         let create_info = VkFenceCreateInfo {
@@ -60,27 +60,25 @@ impl Fence {
             let mut fence = MaybeUninit::<VkFence>::uninit();
 
             vkCreateFence(device.as_raw(), &create_info, ptr::null_mut(), fence.as_mut_ptr())
-                .check_err("create fence");
+                .check_err("create fence")?;
 
             fence.assume_init()
         };
 
-        Self {
+        Ok(Self {
             raw,
             device: device.as_raw(),
-        }
+        })
     }
 
-    pub fn wait(&self) {
+    pub fn wait(&self) -> Result<(), Error> {
         unsafe {
-            vkWaitForFences(self.device, 1, &self.raw, 1, u64::MAX);
+            vkWaitForFences(self.device, 1, &self.raw, 1, u64::MAX).check_err("wait for fence")
         }
     }
 
-    pub fn reset(&mut self) {
-        unsafe {
-            vkResetFences(self.device, 1, &self.raw);
-        }
+    pub fn reset(&mut self) -> Result<(), Error> {
+        unsafe { vkResetFences(self.device, 1, &self.raw).check_err("reset fence") }
     }
 
     pub fn as_raw(&self) -> VkFence {
@@ -1,14 +1,21 @@
 use glfw_sys::*;
+
+use crate::{Error, ErrorKind};
 use std::ffi::{c_char, CStr, CString};
+use std::mem::MaybeUninit;
 use std::ptr;
 
 pub(crate) trait CheckVkError {
-    fn check_err(self, action: &'static str);
+    fn check_err(self, action: &'static str) -> Result<(), Error>;
 }
 
 impl CheckVkError for VkResult {
-    fn check_err(self, action: &'static str) {
-        assert!(self == VK_SUCCESS, "Failed to {}: err = {}", action, self);
+    fn check_err(self, action: &'static str) -> Result<(), Error> {
+        if self == VK_SUCCESS {
+            Ok(())
+        } else {
+            Err(Error::new(action, ErrorKind::from_result(self)))
+        }
     }
 }
 
@@ -16,69 +23,96 @@ pub(crate) fn convert_to_c_ptrs(cstrings: &[CString]) -> Vec<*const c_char> {
     cstrings.iter().map(|cstring| cstring.as_c_str().as_ptr()).collect()
 }
 
+pub(crate) fn find_memory_type(
+    phys_device: VkPhysicalDevice,
+    req_type: u32,
+    req_properties: u32,
+) -> Result<u32, Error> {
+    let mem_properties = unsafe {
+        let mut properties = MaybeUninit::<VkPhysicalDeviceMemoryProperties>::uninit();
+        vkGetPhysicalDeviceMemoryProperties(phys_device, properties.as_mut_ptr());
+        properties.assume_init()
+    };
+
+    for i in 0..mem_properties.memoryTypeCount {
+        if req_type & (1 << i) == 0 {
+            continue;
+        }
+
+        if mem_properties.memoryTypes[i as usize].propertyFlags & req_properties == 0 {
+            continue;
+        }
+
+        return Ok(i);
+    }
+
+    Err(Error::new("find memory type", ErrorKind::NoSuitableMemoryType))
+}
+
+pub fn present_mode_name(mode: VkPresentModeKHR) -> &'static str {
+    match mode {
+        VK_PRESENT_MODE_IMMEDIATE_KHR => "Immediate",
+        VK_PRESENT_MODE_MAILBOX_KHR => "Mailbox",
+        VK_PRESENT_MODE_FIFO_KHR => "FIFO",
+        VK_PRESENT_MODE_FIFO_RELAXED_KHR => "FIFO relaxed",
+        VK_PRESENT_MODE_SHARED_DEMAND_REFRESH_KHR => "Shared on-demand refresh",
+        VK_PRESENT_MODE_SHARED_CONTINUOUS_REFRESH_KHR => "Shared continuous refresh",
+        _ => "Unknown",
+    }
+}
+
 #[allow(clippy::cast_precision_loss)]
 pub fn u32_to_f32_nowarn(x: u32) -> f32 {
     let mantissa = x & 0x007f_ffff; // 23 set bits
     mantissa as f32
 }
 
-pub(crate) fn get_validation_layers(verbose: bool) -> Vec<CString> {
+/// Returns the installed ones of the `requested` layers, warning about the rest
+pub(crate) fn get_validation_layers(requested: &[String]) -> Result<Vec<CString>, Error> {
+    if requested.is_empty() {
+        return Ok(Vec::new());
+    }
+
     let supported_layers = unsafe {
         let mut count = 0;
-        vkEnumerateInstanceLayerProperties(&mut count, ptr::null_mut());
+        vkEnumerateInstanceLayerProperties(&mut count, ptr::null_mut())
+            .check_err("get number of validation layers")?;
 
         let mut layers = Vec::with_capacity(count as usize);
         layers.resize(count as usize, VkLayerProperties::default());
 
-        vkEnumerateInstanceLayerProperties(&mut count, layers.as_mut_ptr());
+        vkEnumerateInstanceLayerProperties(&mut count, layers.as_mut_ptr())
+            .check_err("get validation layers")?;
 
         layers
     };
 
-    if verbose {
-        print_validation_layers(&supported_layers);
-    }
-
-    // return supported_layers
-    //     .iter()
-    //     .map(|layer| unsafe { CStr::from_ptr(layer.layerName.as_ptr()).to_owned() })
-    //     .collect();
+    print_validation_layers(&supported_layers);
 
-    let required_names = vec![
-        // "VK_LAYER_LUNARG_api_dump",
-        "VK_LAYER_MESA_device_select",
-        "VK_LAYER_LUNARG_monitor",
-        "VK_LAYER_KHRONOS_synchronization2",
-        "VK_LAYER_KHRONOS_validation",
-    ];
+    let mut layers = Vec::with_capacity(requested.len());
 
-    // Ensure all required validation layers are supported
-    for req_name in &required_names {
-        let mut supported = false;
-
-        for supp_layer in &supported_layers {
+    for req_name in requested {
+        let supported = supported_layers.iter().any(|supp_layer| {
             let cstr = unsafe { CStr::from_ptr(supp_layer.layerName.as_ptr()) };
-            let name = cstr.to_str().expect("invalid layer name");
+            cstr.to_bytes() == req_name.as_bytes()
+        });
 
-            if req_name == &name {
-                supported = true;
-                break;
-            }
+        match CString::new(req_name.as_str()) {
+            Ok(name) if supported => layers.push(name),
+            _ => eprintln!("Validation layer not found, skipping: {:?}", req_name),
         }
-
-        assert!(supported, "Required validation layer not found: {:?}", req_name);
     }
 
-    required_names.into_iter().map(|name| CString::new(name).unwrap()).collect()
+    Ok(layers)
 }
 
 fn print_validation_layers(layers: &[VkLayerProperties]) {
-    println!("Validation layers:");
+    eprintln!("Validation layers:");
 
     for layer in layers {
         let name = unsafe { CStr::from_ptr(layer.layerName.as_ptr()) };
         let desc = unsafe { CStr::from_ptr(layer.description.as_ptr()) };
 
-        println!("\t{:?}: {:?}", name, desc);
+        eprintln!("\t{:?}: {:?}", name, desc);
     }
 }